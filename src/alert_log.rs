@@ -0,0 +1,79 @@
+//! Append-only JSON Lines log of alert events (`--alert-file`).
+
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::output::format_timestamp;
+use crate::state_text;
+use little_bat::provider::BatteryInfo;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AlertKind {
+    LowBattery,
+    HighDischargeRate,
+    StateChange,
+}
+
+impl AlertKind {
+    fn name(self) -> &'static str {
+        match self {
+            AlertKind::LowBattery => "low_battery",
+            AlertKind::HighDischargeRate => "high_discharge_rate",
+            AlertKind::StateChange => "state_change",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AlertRecord {
+    ts: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    charge: f32,
+    state: String,
+}
+
+pub struct AlertLog {
+    file: File,
+    /// Conditions that were already logged and haven't cleared since.
+    active: HashSet<AlertKind>,
+}
+
+impl AlertLog {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file,
+            active: HashSet::new(),
+        })
+    }
+
+    /// Logs `kind` when its condition becomes true; repeated ticks in the same
+    /// condition are not logged again until it clears.
+    pub fn update(&mut self, kind: AlertKind, active: bool, info: &BatteryInfo) {
+        if !active {
+            self.active.remove(&kind);
+        } else if self.active.insert(kind) {
+            self.append(kind, info);
+        }
+    }
+
+    /// Logs a one-off event such as a state change.
+    pub fn append(&mut self, kind: AlertKind, info: &BatteryInfo) {
+        let record = AlertRecord {
+            ts: format_timestamp(SystemTime::now()),
+            kind: kind.name(),
+            charge: info.charge,
+            state: state_text(info.state),
+        };
+        // A full disk or revoked permissions shouldn't take the display down with it.
+        if let Ok(line) = serde_json::to_string(&record) {
+            let _ = writeln!(self.file, "{line}");
+        }
+    }
+}
@@ -0,0 +1,338 @@
+//! Optional TOML config file providing defaults for the display flags.
+//!
+//! Looked up at `$LITTLE_BAT_CONFIG`, then `$XDG_CONFIG_HOME/little-bat/config.toml`,
+//! then `~/.config/little-bat/config.toml`. Command-line flags take precedence.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use ratatui::style::Modifier;
+use serde::Deserialize;
+
+use crate::styles::Rgb;
+use crate::{Args, parse_color, parse_label_style};
+
+pub const CONFIG_ENV: &str = "LITTLE_BAT_CONFIG";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub graphic: Option<bool>,
+    pub label: Option<bool>,
+    pub label_style: Option<String>,
+    pub icons: Option<bool>,
+    pub large_digits: Option<bool>,
+    pub invert: Option<bool>,
+    pub invert_colors: Option<bool>,
+    pub charge_limit: Option<f32>,
+    pub calibration_hint: Option<bool>,
+    pub refresh_on_wake: Option<bool>,
+    /// `--two-color` ends as hex or CSS names; either one alone keeps the default for the other.
+    pub high_color: Option<String>,
+    pub low_color: Option<String>,
+}
+
+/// `--two-color` ends used when the config only sets one of them.
+const DEFAULT_HIGH_COLOR: Rgb = (0x27, 0xAE, 0x60);
+const DEFAULT_LOW_COLOR: Rgb = (0xE7, 0x4C, 0x3C);
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    Invalid {
+        path: PathBuf,
+        problems: Vec<String>,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "cannot read {}: {source}", path.display())
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "cannot parse {}: {source}", path.display())
+            }
+            ConfigError::Invalid { path, problems } => {
+                write!(
+                    f,
+                    "invalid config {}: {}",
+                    path.display(),
+                    problems.join("; ")
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// A config file together with where it was loaded from, if anywhere.
+#[derive(Debug, Default)]
+pub struct LoadedConfig {
+    pub path: Option<PathBuf>,
+    pub config: Config,
+}
+
+/// Resolves the config path from `--config`, the environment, or the XDG default.
+///
+/// An explicitly given path must exist; the default location is optional.
+fn config_path(explicit: Option<&Path>) -> Option<(PathBuf, bool)> {
+    if let Some(path) = explicit {
+        return Some((path.to_path_buf(), true));
+    }
+    if let Some(path) = env::var_os(CONFIG_ENV) {
+        return Some((PathBuf::from(path), true));
+    }
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some((base.join("little-bat").join("config.toml"), false))
+}
+
+pub fn load(explicit: Option<&Path>) -> Result<LoadedConfig, ConfigError> {
+    let Some((path, required)) = config_path(explicit) else {
+        return Ok(LoadedConfig::default());
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+            return Ok(LoadedConfig::default());
+        }
+        Err(source) => return Err(ConfigError::Io { path, source }),
+    };
+    let config = toml::from_str(&text).map_err(|source| ConfigError::Parse {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(LoadedConfig {
+        path: Some(path),
+        config,
+    })
+}
+
+impl Config {
+    /// Values that would be rejected if given on the command line.
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(style) = &self.label_style
+            && let Err(err) = parse_label_style(style)
+        {
+            problems.push(format!("label_style: {err}"));
+        }
+        if let Some(limit) = self.charge_limit
+            && !(0.0..=100.0).contains(&limit)
+        {
+            problems.push(format!("charge_limit: {limit} is not between 0 and 100"));
+        }
+        problems
+    }
+
+    /// Settings that are valid but have no effect.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.invert_colors == Some(true) && self.invert != Some(true) {
+            warnings.push("invert_colors has no effect unless invert is also set".to_string());
+        }
+        for (key, color) in [
+            ("high_color", &self.high_color),
+            ("low_color", &self.low_color),
+        ] {
+            if let Some(color) = color
+                && parse_color(color).is_err()
+            {
+                warnings.push(format!("{key}: unknown color `{color}`, using the default"));
+            }
+        }
+        warnings
+    }
+}
+
+impl LoadedConfig {
+    /// Fills in every setting not given on the command line from the config file.
+    pub fn apply(&self, args: &mut Args) -> Result<(), ConfigError> {
+        let config = &self.config;
+        let problems = config.problems();
+        if !problems.is_empty() {
+            return Err(ConfigError::Invalid {
+                path: self.path.clone().unwrap_or_default(),
+                problems,
+            });
+        }
+
+        args.graphic |= config.graphic.unwrap_or(false);
+        args.label |= config.label.unwrap_or(false);
+        args.icons |= config.icons.unwrap_or(false);
+        args.large_digits |= config.large_digits.unwrap_or(false);
+        args.invert |= config.invert.unwrap_or(false);
+        args.invert_colors |= args.invert && config.invert_colors.unwrap_or(false);
+        args.no_calibration_hint |= config.calibration_hint == Some(false);
+        args.refresh_on_wake |= config.refresh_on_wake.unwrap_or(false);
+        args.charge_limit = args.charge_limit.or(config.charge_limit);
+        if args.label_style.is_none()
+            && let Some(style) = &config.label_style
+        {
+            args.label_style = parse_label_style(style).ok();
+        }
+        if args.two_color.is_none() && (config.high_color.is_some() || config.low_color.is_some()) {
+            let color = |key: &str, value: &Option<String>, default: Rgb| match value {
+                Some(name) => parse_color(name).unwrap_or_else(|err| {
+                    log::warn!("config {key}: {err}, using the default");
+                    default
+                }),
+                None => default,
+            };
+            args.two_color = Some(vec![
+                color("high_color", &config.high_color, DEFAULT_HIGH_COLOR),
+                color("low_color", &config.low_color, DEFAULT_LOW_COLOR),
+            ]);
+        }
+        Ok(())
+    }
+}
+
+/// Implements `little-bat validate-config`: reports problems and the effective settings.
+pub fn validate(args: &Args, loaded: &LoadedConfig) -> ExitCode {
+    match &loaded.path {
+        Some(path) => println!("Config file: {}", path.display()),
+        None => println!("Config file: none found, using defaults"),
+    }
+    if let Ok(path) = env::var(CONFIG_ENV) {
+        println!("  (selected by ${CONFIG_ENV}={path})");
+    }
+
+    let config = &loaded.config;
+    for warning in config.warnings() {
+        println!("warning: {warning}");
+    }
+    let problems = config.problems();
+    for problem in &problems {
+        println!("error: {problem}");
+    }
+
+    let mut effective = args.clone();
+    if problems.is_empty() {
+        // Cannot fail: `apply` only rejects the problems checked above.
+        let _ = loaded.apply(&mut effective);
+    }
+
+    println!();
+    println!("Effective settings:");
+    let source = |cli: bool, file: bool| match (cli, file) {
+        (true, _) => "command line",
+        (false, true) => "config",
+        (false, false) => "default",
+    };
+    let settings = [
+        (
+            "graphic",
+            effective.graphic.to_string(),
+            source(args.graphic, config.graphic.is_some()),
+        ),
+        (
+            "label",
+            effective.label.to_string(),
+            source(args.label, config.label.is_some()),
+        ),
+        (
+            "label_style",
+            label_style_name(effective.label_style.unwrap_or_default()),
+            source(args.label_style.is_some(), config.label_style.is_some()),
+        ),
+        (
+            "icons",
+            effective.icons.to_string(),
+            source(args.icons, config.icons.is_some()),
+        ),
+        (
+            "large_digits",
+            effective.large_digits.to_string(),
+            source(args.large_digits, config.large_digits.is_some()),
+        ),
+        (
+            "invert",
+            effective.invert.to_string(),
+            source(args.invert, config.invert.is_some()),
+        ),
+        (
+            "invert_colors",
+            effective.invert_colors.to_string(),
+            source(args.invert_colors, config.invert_colors.is_some()),
+        ),
+        (
+            "charge_limit",
+            effective
+                .charge_limit
+                .map_or("none".to_string(), |l| format!("{l}%")),
+            source(args.charge_limit.is_some(), config.charge_limit.is_some()),
+        ),
+        (
+            "calibration_hint",
+            (!effective.no_calibration_hint).to_string(),
+            source(args.no_calibration_hint, config.calibration_hint.is_some()),
+        ),
+        (
+            "refresh_on_wake",
+            effective.refresh_on_wake.to_string(),
+            source(args.refresh_on_wake, config.refresh_on_wake.is_some()),
+        ),
+        (
+            "two_color",
+            effective
+                .two_color
+                .as_deref()
+                .map_or("none".to_string(), |colors| {
+                    colors
+                        .iter()
+                        .map(|(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}"))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }),
+            source(
+                args.two_color.is_some(),
+                config.high_color.is_some() || config.low_color.is_some(),
+            ),
+        ),
+    ];
+    for (name, value, source) in settings {
+        println!("  {name:<16} = {value:<12} ({source})");
+    }
+
+    if problems.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// The `--label-style` spelling of `modifier`, e.g. `bold,underline`.
+fn label_style_name(modifier: Modifier) -> String {
+    let names = [
+        (Modifier::BOLD, "bold"),
+        (Modifier::ITALIC, "italic"),
+        (Modifier::UNDERLINED, "underline"),
+        (Modifier::DIM, "dim"),
+    ];
+    let set: Vec<_> = names
+        .into_iter()
+        .filter(|(flag, _)| modifier.contains(*flag))
+        .map(|(_, name)| name)
+        .collect();
+    if set.is_empty() {
+        "plain".to_string()
+    } else {
+        set.join(",")
+    }
+}
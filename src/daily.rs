@@ -0,0 +1,132 @@
+//! Time spent in each 10% charge bracket per day, persisted across sessions.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Bar, BarChart, BarGroup},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::draw_widget;
+use crate::output::format_date;
+
+const BRACKETS: usize = 10;
+
+/// Write accumulated time to disk at most this often.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Gaps longer than this (e.g. a suspend) aren't counted as time at the current charge.
+const MAX_SAMPLE_GAP: Duration = Duration::from_secs(60);
+
+pub const CHART_WIDTH: u16 = 40;
+pub const CHART_HEIGHT: u16 = 8;
+
+/// Minutes per charge bracket (0–10%, 10–20%, … 90–100%), keyed by UTC date.
+#[derive(Default, Deserialize, Serialize)]
+struct StatsFile {
+    days: BTreeMap<String, [f64; BRACKETS]>,
+}
+
+pub struct DailyStats {
+    path: PathBuf,
+    file: StatsFile,
+    last_sample: Option<Instant>,
+    last_save: Instant,
+}
+
+impl DailyStats {
+    /// Loads existing stats from `path`, or starts empty if the file doesn't exist yet.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let file = match fs::read_to_string(path) {
+            Ok(text) => serde_json::from_str(&text).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {err}", path.display()),
+                )
+            })?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => StatsFile::default(),
+            Err(err) => {
+                return Err(io::Error::new(
+                    err.kind(),
+                    format!("{}: {err}", path.display()),
+                ));
+            }
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            last_sample: None,
+            last_save: Instant::now(),
+        })
+    }
+
+    /// Adds the time since the previous sample to the bracket for `charge`.
+    pub fn record(&mut self, charge: f32) {
+        let now = Instant::now();
+        if let Some(last) = self.last_sample.replace(now) {
+            let elapsed = now.duration_since(last);
+            if elapsed <= MAX_SAMPLE_GAP {
+                let bracket = ((charge / 10.0) as usize).min(BRACKETS - 1);
+                let today = format_date(SystemTime::now());
+                let minutes = self.file.days.entry(today).or_default();
+                minutes[bracket] += elapsed.as_secs_f64() / 60.0;
+            }
+        }
+        if now.duration_since(self.last_save) >= SAVE_INTERVAL {
+            // Keep tracking in memory if the file can't be written; the next save retries.
+            let _ = self.save();
+        }
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        self.last_save = Instant::now();
+        let json = serde_json::to_string_pretty(&self.file).map_err(io::Error::other)?;
+        fs::write(&self.path, json)
+    }
+
+    /// Average minutes per day spent in each bracket, over all recorded days.
+    fn averages(&self) -> [f64; BRACKETS] {
+        let mut totals = [0.0; BRACKETS];
+        for minutes in self.file.days.values() {
+            for (total, m) in totals.iter_mut().zip(minutes) {
+                *total += m;
+            }
+        }
+        let days = self.file.days.len().max(1) as f64;
+        totals.map(|total| total / days)
+    }
+
+    pub fn render(&self, area: Rect, buf: &mut Buffer, transparent: bool) {
+        let bars: Vec<Bar> = self
+            .averages()
+            .iter()
+            .enumerate()
+            .map(|(i, minutes)| {
+                Bar::default()
+                    .value(minutes.round() as u64)
+                    .label(format!("{}", i * 10).into())
+            })
+            .collect();
+        let chart = BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(Color::Cyan))
+            .value_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+            .label_style(Style::default().fg(Color::DarkGray));
+        draw_widget(chart, area, buf, transparent);
+    }
+}
+
+impl Drop for DailyStats {
+    fn drop(&mut self) {
+        let _ = self.save();
+    }
+}
@@ -0,0 +1,143 @@
+//! `--fifo`: answers `status`, `charge`, `state` and `quit` on a named pipe.
+//!
+//! Each command is answered on the same pipe once the client opens it for reading:
+//!
+//! ```sh
+//! echo charge > /tmp/little-bat.fifo && cat /tmp/little-bat.fifo
+//! ```
+
+use std::ffi::CString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileTypeExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// The answers for the latest reading.
+#[derive(Clone, Default)]
+pub struct Status {
+    pub status: String,
+    pub charge: String,
+    pub state: String,
+}
+
+pub struct Fifo {
+    path: PathBuf,
+    status: Arc<Mutex<Status>>,
+    quit: Arc<AtomicBool>,
+    /// Whether this run made the pipe, and so should remove it on exit.
+    created: bool,
+}
+
+impl Fifo {
+    /// Creates the pipe at `path` (or reuses an existing one) and starts answering on it.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let created = match fs::metadata(path) {
+            // A pipe left by an earlier run.
+            Ok(meta) if meta.file_type().is_fifo() => false,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} exists and is not a named pipe", path.display()),
+                ));
+            }
+            Err(_) => {
+                make_fifo(path)?;
+                true
+            }
+        };
+
+        let fifo = Self {
+            path: path.to_path_buf(),
+            status: Arc::default(),
+            quit: Arc::default(),
+            created,
+        };
+        let (path, status, quit) = (fifo.path.clone(), fifo.status.clone(), fifo.quit.clone());
+        thread::spawn(move || {
+            if let Err(err) = serve(&path, &status, &quit) {
+                log::warn!("--fifo {} stopped answering: {err}", path.display());
+            }
+        });
+        Ok(fifo)
+    }
+
+    pub fn publish(&self, status: Status) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status;
+        }
+    }
+
+    /// Whether a client sent `quit`.
+    pub fn quit_requested(&self) -> bool {
+        self.quit.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Fifo {
+    fn drop(&mut self) {
+        if self.created {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Creates a named pipe at `path` that only this user can use.
+fn make_fifo(path: &Path) -> io::Result<()> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    // SAFETY: `c_path` is a valid NUL-terminated string for the duration of the call.
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Reads one command per client, then closes the read end before replying so the
+/// answer isn't read back as the next command. A client that never reads gets no
+/// reply, so a stray `echo` can't stall the pipe.
+fn serve(path: &Path, status: &Mutex<Status>, quit: &AtomicBool) -> io::Result<()> {
+    loop {
+        let mut command = String::new();
+        BufReader::new(File::open(path)?).read_line(&mut command)?;
+        let reply = match command.trim() {
+            "" => continue,
+            "status" => status.lock().map(|s| s.status.clone()).unwrap_or_default(),
+            "charge" => status.lock().map(|s| s.charge.clone()).unwrap_or_default(),
+            "state" => status.lock().map(|s| s.state.clone()).unwrap_or_default(),
+            "quit" => "ok".to_string(),
+            other => format!("error: unknown command `{other}`"),
+        };
+        if command.trim() == "quit" {
+            quit.store(true, Ordering::Relaxed);
+        }
+        if let Some(mut writer) = open_reader_end(path)? {
+            writeln!(writer, "{reply}")?;
+        }
+    }
+}
+
+/// Opens `path` for writing once a client has it open for reading, giving up after
+/// half a second.
+fn open_reader_end(path: &Path) -> io::Result<Option<File>> {
+    for _ in 0..50 {
+        let writer = OpenOptions::new()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path);
+        match writer {
+            Ok(writer) => return Ok(Some(writer)),
+            // No reader yet.
+            Err(err) if err.raw_os_error() == Some(libc::ENXIO) => {
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(None)
+}
@@ -0,0 +1,167 @@
+//! Recent charge readings and the sparkline/chart views that display them.
+
+use std::collections::VecDeque;
+
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Style},
+    symbols::Marker,
+    text::Line,
+    widgets::{Axis, Chart, Dataset, GraphType, Paragraph, Sparkline},
+};
+
+use crate::{draw_widget, styles};
+
+const SPARKLINE_HEIGHT: u16 = 3;
+/// Width of the sideways sparkline, where each row is one reading.
+const SIDE_SPARKLINE_WIDTH: u16 = 10;
+const CHART_HEIGHT: u16 = 10;
+const CHART_MIN_WIDTH: u16 = 40;
+
+/// The last `capacity` charge readings, oldest first.
+///
+/// Each reading takes 4 bytes, so the default `--max-readings` of 3600 costs about 14 KiB.
+pub struct History {
+    readings: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            readings: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, charge: f32) {
+        while self.readings.len() >= self.capacity {
+            self.readings.pop_front();
+        }
+        self.readings.push_back(charge);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn clear(&mut self) {
+        self.readings.clear();
+    }
+}
+
+/// Where `--history` goes relative to the reading.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SparklinePosition {
+    Above,
+    #[default]
+    Below,
+    /// In a column beside the reading; the sparkline runs top (oldest) to bottom
+    Left,
+    Right,
+}
+
+impl SparklinePosition {
+    pub fn beside(self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HistoryView {
+    Sparkline,
+    Chart,
+}
+
+impl HistoryView {
+    pub fn toggled(self) -> Self {
+        match self {
+            HistoryView::Sparkline => HistoryView::Chart,
+            HistoryView::Chart => HistoryView::Sparkline,
+        }
+    }
+
+    /// Preferred width and height of the view for a history of `capacity` readings,
+    /// with the sparkline turned sideways when `beside` the reading.
+    pub fn size(self, capacity: usize, beside: bool) -> (u16, u16) {
+        let capacity = u16::try_from(capacity).unwrap_or(u16::MAX);
+        match self {
+            HistoryView::Sparkline if beside => (SIDE_SPARKLINE_WIDTH, capacity),
+            HistoryView::Sparkline => (capacity, SPARKLINE_HEIGHT),
+            HistoryView::Chart => (capacity.max(CHART_MIN_WIDTH), CHART_HEIGHT),
+        }
+    }
+}
+
+pub fn render(
+    history: &History,
+    view: HistoryView,
+    beside: bool,
+    color: Color,
+    area: Rect,
+    buf: &mut Buffer,
+    transparent: bool,
+) {
+    match view {
+        HistoryView::Sparkline if beside => {
+            // One row per reading, the most recent at the bottom, as a bar of 1/8 cells.
+            let skip = history.readings.len().saturating_sub(area.height.into());
+            let rows: Vec<Line> = history
+                .readings
+                .iter()
+                .skip(skip)
+                .map(|&charge| Line::from(styles::smooth_bar(charge, area.width.into())))
+                .collect();
+            let sparkline = Paragraph::new(rows).style(Style::default().fg(color));
+            draw_widget(sparkline, area, buf, transparent);
+        }
+        HistoryView::Sparkline => {
+            // Show the most recent readings that fit.
+            let skip = history.readings.len().saturating_sub(area.width.into());
+            let data: Vec<u64> = history
+                .readings
+                .iter()
+                .skip(skip)
+                .map(|&charge| charge.round() as u64)
+                .collect();
+            let sparkline = Sparkline::default()
+                .data(&data)
+                .max(100)
+                .style(Style::default().fg(color));
+            draw_widget(sparkline, area, buf, transparent);
+        }
+        HistoryView::Chart => {
+            // Right-aligned, so the newest reading sits at "now" before the history fills.
+            let start = history.capacity.saturating_sub(history.readings.len());
+            let points: Vec<(f64, f64)> = history
+                .readings
+                .iter()
+                .enumerate()
+                .map(|(i, &charge)| ((start + i) as f64, f64::from(charge)))
+                .collect();
+            let span = history.capacity.saturating_sub(1) as f64;
+            let dataset = Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(color))
+                .data(&points);
+            let label_style = Style::default().fg(Color::DarkGray);
+            let chart = Chart::new(vec![dataset])
+                .x_axis(
+                    Axis::default()
+                        .bounds([0.0, span])
+                        .labels([format!("-{}s", history.capacity), "now".to_string()])
+                        .style(label_style),
+                )
+                .y_axis(
+                    Axis::default()
+                        .bounds([0.0, 100.0])
+                        .labels(["0%", "50%", "100%"])
+                        .style(label_style),
+                );
+            draw_widget(chart, area, buf, transparent);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Placement of the widget within the terminal area.
+
+use ratatui::layout::{Constraint, Flex, Layout, Rect};
+
+/// A corner of the terminal area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Corner {
+    TopRight,
+}
+
+/// A `width` × `height` rect in the middle of `area`, clipped to it.
+pub(crate) fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
+
+/// A `width` × `height` rect in the given corner of `area`, clipped to it.
+pub(crate) fn corner_rect(position: Corner, area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    let (x, y) = match position {
+        Corner::TopRight => (area.right() - width, area.y),
+    };
+    Rect::new(x, y, width, height)
+}
+
+/// Like [`centered_rect`], with the size first clamped to the given bounds.
+///
+/// If the bounds conflict, the maximum wins.
+pub(crate) fn constrained_rect(
+    min_width: u16,
+    max_width: u16,
+    min_height: u16,
+    max_height: u16,
+    area: Rect,
+    width: u16,
+    height: u16,
+) -> Rect {
+    centered_rect(
+        area,
+        width.max(min_width).min(max_width),
+        height.max(min_height).min(max_height),
+    )
+}
+
+/// Moves `rect` by `(dx, dy)` cells, keeping it inside `area`.
+pub(crate) fn offset_rect(rect: Rect, area: Rect, (dx, dy): (i16, i16)) -> Rect {
+    let shift = |pos: u16, delta: i16, min: u16, max: u16| {
+        (i32::from(pos) + i32::from(delta)).clamp(i32::from(min), i32::from(max.max(min))) as u16
+    };
+    Rect {
+        x: shift(rect.x, dx, area.x, area.right().saturating_sub(rect.width)),
+        y: shift(
+            rect.y,
+            dy,
+            area.y,
+            area.bottom().saturating_sub(rect.height),
+        ),
+        ..rect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect::new(0, 0, 80, 24);
+
+    #[test]
+    fn centered_rect_centers_within_area() {
+        assert_eq!(centered_rect(AREA, 20, 4), Rect::new(30, 10, 20, 4));
+        let offset = Rect::new(10, 5, 40, 10);
+        assert_eq!(centered_rect(offset, 10, 2), Rect::new(25, 9, 10, 2));
+    }
+
+    #[test]
+    fn centered_rect_clips_to_small_areas() {
+        let small = Rect::new(2, 3, 10, 2);
+        assert_eq!(centered_rect(small, 20, 4), small);
+        let empty = Rect::new(5, 5, 0, 0);
+        assert_eq!(centered_rect(empty, 20, 4).area(), 0);
+    }
+
+    #[test]
+    fn corner_rect_places_in_the_corner() {
+        assert_eq!(
+            corner_rect(Corner::TopRight, AREA, 20, 4),
+            Rect::new(60, 0, 20, 4)
+        );
+        let inner = Rect::new(10, 5, 30, 10);
+        assert_eq!(
+            corner_rect(Corner::TopRight, inner, 1, 1),
+            Rect::new(39, 5, 1, 1)
+        );
+    }
+
+    #[test]
+    fn corner_rect_clips_to_small_areas() {
+        let small = Rect::new(2, 3, 10, 2);
+        assert_eq!(corner_rect(Corner::TopRight, small, 20, 4), small);
+        let empty = Rect::new(5, 5, 0, 0);
+        assert_eq!(corner_rect(Corner::TopRight, empty, 20, 4), empty);
+    }
+
+    #[test]
+    fn constrained_rect_applies_bounds() {
+        assert_eq!(constrained_rect(20, 80, 0, 24, AREA, 10, 4).width, 20);
+        assert_eq!(constrained_rect(20, 30, 0, 24, AREA, 50, 4).width, 30);
+        assert_eq!(constrained_rect(0, 80, 6, 8, AREA, 10, 2).height, 6);
+        // Conflicting bounds: the maximum wins.
+        assert_eq!(constrained_rect(40, 30, 0, 24, AREA, 10, 4).width, 30);
+    }
+
+    #[test]
+    fn constrained_rect_clips_to_small_areas() {
+        let small = Rect::new(0, 0, 10, 2);
+        assert_eq!(constrained_rect(20, 80, 4, 24, small, 10, 2), small);
+        assert_eq!(
+            constrained_rect(20, 80, 4, 24, Rect::ZERO, 10, 2),
+            Rect::ZERO
+        );
+    }
+
+    #[test]
+    fn offset_rect_stays_inside_area() {
+        let rect = Rect::new(30, 10, 20, 4);
+        assert_eq!(offset_rect(rect, AREA, (5, -2)), Rect::new(35, 8, 20, 4));
+        assert_eq!(
+            offset_rect(rect, AREA, (100, 100)),
+            Rect::new(60, 20, 20, 4)
+        );
+        assert_eq!(
+            offset_rect(rect, AREA, (-100, -100)),
+            Rect::new(0, 0, 20, 4)
+        );
+    }
+}
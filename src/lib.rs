@@ -0,0 +1,62 @@
+//! Battery readings without the TUI, for programs that want to drive their own display.
+
+#[cfg(feature = "async")]
+pub mod nonblocking;
+mod platform;
+pub mod provider;
+
+use provider::{BatteryInfo, BatteryProvider};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest single sleep in [`run_headless`], bounding how long a cancel takes to notice.
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Reads `provider` every `interval` and hands each reading to `on_update` until
+/// `cancel` is set, typically from another thread through an `Arc<AtomicBool>`.
+///
+/// `on_update` gets one entry per battery found, so an empty list means no battery.
+/// Returns early with the first read error.
+pub fn run_headless(
+    provider: impl BatteryProvider,
+    interval: Duration,
+    cancel: &AtomicBool,
+    mut on_update: impl FnMut(Vec<BatteryInfo>),
+) -> Result<(), battery::Error> {
+    while !cancel.load(Ordering::Relaxed) {
+        let started = Instant::now();
+        on_update(provider.battery_info()?.into_iter().collect());
+        while !cancel.load(Ordering::Relaxed) {
+            let remaining = interval.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(CANCEL_CHECK_INTERVAL));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use provider::SimulatedBatteryProvider;
+
+    #[test]
+    fn run_headless_stops_when_cancelled() {
+        let provider = SimulatedBatteryProvider::new(BatteryInfo {
+            charge: 42.0,
+            ..BatteryInfo::default()
+        });
+        let cancel = AtomicBool::new(false);
+        let mut updates = Vec::new();
+        run_headless(provider, Duration::from_secs(60), &cancel, |readings| {
+            updates.push(readings);
+            cancel.store(true, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0][0].charge, 42.0);
+    }
+}
@@ -1,167 +1,2511 @@
-use battery::{units::ratio::percent, Manager, State};
-use clap::Parser;
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+mod alert_log;
+mod config;
+mod daily;
+#[cfg(unix)]
+mod fifo;
+mod history;
+mod layout;
+mod notify;
+mod output;
+mod poll;
+mod styles;
+mod suspend;
+mod term;
+mod trend;
+mod version_check;
+
+use alert_log::{AlertKind, AlertLog};
+use battery::State;
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum};
+use crossterm::event::{KeyCode, KeyEventKind};
 use ratatui::{
-    layout::{Constraint, Flex, Layout, Rect},
-    style::{Color, Style},
-    text::{Line, Span},
-    widgets::Paragraph,
     DefaultTerminal, Frame,
+    buffer::Buffer,
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Gauge, Paragraph, Widget},
 };
-use std::time::Duration;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufWriter, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use daily::DailyStats;
+#[cfg(unix)]
+use fifo::Fifo;
+use history::{History, HistoryView, SparklinePosition};
+use layout::{Corner, centered_rect, offset_rect};
+use little_bat::provider::{
+    BatteryInfo, BatteryProvider, ReplayBatteryProvider, SimulatedBatteryProvider,
+    SysfsBatteryProvider, SystemBatteryProvider,
+};
+use notify::{Notifier, NotifyEvent, Sound};
+use poll::{PollStrategy, Waiter};
+use styles::{BarStyle, ChargingAnimation, ColorMode, Rgb};
+use suspend::SuspendDetector;
+use term::TermCapabilities;
+use trend::Trend;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Charge above which the display is green.
+const WARNING_THRESHOLD: f32 = 50.0;
+
+/// Charge at or below which the display is red and the battery is considered low.
+const CRITICAL_THRESHOLD: f32 = 20.0;
+
+/// Redraw interval for animated display modes.
+const ANIMATION_FRAME: Duration = Duration::from_millis(100);
+
+/// Frames per brightness cycle of `--charging-animation pulse`.
+const PULSE_PERIOD: u64 = 20;
+
+/// How often to check for a resume from suspend with `--refresh-on-wake`.
+const WAKE_CHECK_INTERVAL: Duration = Duration::from_millis(200);
 
-#[derive(Parser)]
+/// How long the battery must stay discharging below 5% before hinting at a calibration cycle.
+const CALIBRATION_DISCHARGE: Duration = Duration::from_secs(30 * 60);
+
+/// Exit status when no battery appears within the startup retries, `--charge-only`
+/// has no reading to print, or `--list` finds nothing.
+const EXIT_NO_BATTERY: u8 = 3;
+
+/// How long a flash message such as `[History cleared]` stays up.
+const FLASH_DURATION: Duration = Duration::from_secs(2);
+
+/// Narrowest `--gauge`, so short percentages still get a readable bar.
+const GAUGE_MIN_WIDTH: u16 = 20;
+
+#[derive(Clone, Parser)]
 #[command(name = "little-bat", about = "A minimal TUI battery status display")]
+#[command(group(ArgGroup::new("direction").args(["below", "above"])))]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Config file to load instead of ~/.config/little-bat/config.toml
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
     /// Show ASCII battery graphic instead of just percentage
     #[arg(short, long)]
     graphic: bool,
 
-    /// Show label text (e.g., "Battery:", charging status)
-    #[arg(short, long)]
-    label: bool,
+    /// Draw the charge as a full-width gauge with the percentage inside it
+    #[arg(long, conflicts_with = "graphic")]
+    gauge: bool,
+
+    /// With --graphic, show only the bar without the percentage underneath
+    #[arg(long)]
+    hide_percentage: bool,
+
+    /// Put a state arrow before the --graphic bar: ↑ charging, ↓ discharging, = full/empty
+    #[arg(long)]
+    state_prefix: bool,
+
+    /// Text before the --graphic bar (e.g. `|` or `❮`)
+    #[arg(long, value_name = "STR", default_value = "[")]
+    bar_prefix: String,
+
+    /// Text after the --graphic bar
+    #[arg(long, value_name = "STR", default_value = "]")]
+    bar_suffix: String,
+
+    /// Glyphs for the --graphic bar
+    #[arg(long, value_enum, default_value_t)]
+    bar_style: BarStyle,
+
+    /// Draw the --graphic bar with 1/8-cell partial blocks (ignores --charge-limit)
+    #[arg(long, conflicts_with_all = ["bar_style", "invert"])]
+    unicode_bar: bool,
+
+    /// Animate the --graphic bar while charging
+    #[arg(long, value_enum, default_value_t)]
+    charging_animation: ChargingAnimation,
+
+    /// Show label text (e.g., "Battery:", charging status)
+    #[arg(short, long)]
+    label: bool,
+
+    /// Abbreviate the state to one letter: C, D, F, E or ?
+    #[arg(long)]
+    short_state: bool,
+
+    /// Label text shown above the reading with --label
+    #[arg(long, value_name = "STR", default_value = "Battery")]
+    title: String,
+
+    /// Which side of the widget the text lines snap to
+    #[arg(long, value_enum, default_value_t)]
+    align: TextAlign,
+
+    /// App name header on the widget's first line
+    #[arg(long, value_enum, default_value_t)]
+    title_mode: TitleMode,
+
+    /// Fill the bar from right to left (depleting instead of filling)
+    #[arg(long)]
+    invert: bool,
+
+    /// With --invert, use green for nearly empty and red for nearly full
+    #[arg(long, requires = "invert")]
+    invert_colors: bool,
+
+    /// Blend between two colors by charge instead of green/yellow/red (e.g. `#00ff00 #ff0000`
+    /// or `limegreen orangered`)
+    #[arg(long, num_args = 2, value_names = ["HIGH", "LOW"], value_parser = parse_color)]
+    two_color: Option<Vec<Rgb>>,
+
+    /// Color by state (charging, discharging, full...) instead of by charge level
+    #[arg(long, conflicts_with = "two_color")]
+    color_state_only: bool,
+
+    /// Swap foreground and background colors throughout the widget
+    #[arg(long)]
+    inverse_video: bool,
+
+    /// Text style for labels: bold, italic, underline, dim or plain, comma-separated
+    #[arg(long, value_name = "STYLE", value_parser = parse_label_style)]
+    label_style: Option<Modifier>,
+
+    /// Prefix the display with a charging state icon
+    #[arg(long)]
+    icons: bool,
+
+    /// Icons to use instead of emoji when the terminal looks unable to draw them
+    #[arg(long, value_enum, value_name = "SET")]
+    emoji_fallback: Option<EmojiFallback>,
+
+    /// Send 24-bit colors even when the terminal doesn't advertise support
+    #[arg(long)]
+    true_color: bool,
+
+    /// Assume a fully capable terminal instead of checking TERM, COLORTERM and friends
+    #[arg(long)]
+    no_term_detect: bool,
+
+    /// Render the percentage with fullwidth digits for readability at a distance
+    #[arg(long)]
+    large_digits: bool,
+
+    /// Show charge as a fraction from 0 to 1 (e.g. 0.740) instead of a percentage
+    #[arg(long)]
+    fraction: bool,
+
+    /// Draw the whole widget in bold while charge is below this value (e.g. 15%)
+    #[arg(long, value_name = "PERCENT", value_parser = parse_percent)]
+    bold_below: Option<f32>,
+
+    /// Underline the whole widget at critical charge (20% or below)
+    #[arg(long)]
+    underline_critical: bool,
+
+    /// Blink the widget in these states, comma-separated: charging, discharging, critical, full, unknown
+    #[arg(long, value_name = "STATES", value_parser = parse_blink_states)]
+    blink_state: Option<BTreeSet<BlinkTrigger>>,
+
+    /// Run without the TUI and exit once charge crosses this value (e.g. 20%)
+    #[arg(long, value_name = "N%", value_parser = parse_percent, requires = "direction")]
+    watch_threshold: Option<f32>,
+
+    /// With --watch-threshold, exit when charge drops below the threshold
+    #[arg(long, requires = "watch_threshold")]
+    below: bool,
+
+    /// With --watch-threshold, exit when charge rises above the threshold
+    #[arg(long, requires = "watch_threshold")]
+    above: bool,
+
+    /// Run without the TUI until the charging state changes, then print the new state and exit 0
+    #[arg(long, conflicts_with_all = ["watch_threshold", "once", "output_on_change"])]
+    watch_state_change: bool,
+
+    /// Exit with code 0 once the battery becomes full
+    #[arg(long)]
+    exit_on_full: bool,
+
+    /// Exit with code 2 when the battery switches from charging or full to discharging
+    #[arg(long)]
+    exit_on_discharge: bool,
+
+    /// Exit after this many seconds (code 0, or 1 when --watch-threshold or
+    /// --watch-state-change is not met)
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Keep the display going when --timeout or an --exit-on-* condition would end it
+    #[arg(long)]
+    wrap: bool,
+
+    /// Print the status only if it differs from the one stored in PATH (exit 1 if unchanged)
+    #[arg(long, value_name = "PATH")]
+    once_if_changed: Option<PathBuf>,
+
+    /// Mark a firmware charge limit on the graphic bar (e.g. 80)
+    #[arg(long, value_name = "N", value_parser = parse_percent)]
+    charge_limit: Option<f32>,
+
+    /// Minimum widget width in cells
+    #[arg(long, value_name = "COLS", default_value_t = 20)]
+    min_width: u16,
+
+    /// Maximum widget width in cells
+    #[arg(long, value_name = "COLS", default_value_t = 80)]
+    max_width: u16,
+
+    /// Shift the centered widget by X columns and Y rows (negative is left/up)
+    #[arg(long, num_args = 2, value_names = ["X", "Y"], allow_negative_numbers = true)]
+    center_offset: Option<Vec<i16>>,
+
+    /// Drift the widget around the screen to avoid burn-in on always-on displays
+    #[arg(long = "float", conflicts_with = "center_offset")]
+    float_widget: bool,
+
+    /// Only draw non-space characters, so a transparent terminal background shows through
+    #[arg(long)]
+    transparency: bool,
+
+    /// Cycle the widget through the colors of the rainbow
+    #[cfg(feature = "fun")]
+    #[arg(long)]
+    rainbow: bool,
+
+    /// Keep the last N readings and show them as a sparkline below the widget
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+    history: Option<u64>,
+
+    /// Show the history as a line chart instead of a sparkline (toggle with `g`)
+    #[arg(long, requires = "history")]
+    graph: bool,
+
+    /// Where the --history view goes relative to the reading
+    #[arg(long, value_enum, default_value_t, requires = "history")]
+    sparkline_position: SparklinePosition,
+
+    /// Upper bound on --history, to cap memory use in long sessions
+    #[arg(long, value_name = "N", default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..))]
+    max_readings: u64,
+
+    /// Estimate time to empty or full from the recent charge trend
+    #[arg(long)]
+    forecast: bool,
+
+    /// How --forecast shows the time left
+    #[arg(long, value_enum, default_value_t)]
+    time_format: TimeFormat,
+
+    /// Show how fast the charge is changing, in percent per minute
+    #[arg(long)]
+    charge_rate: bool,
+
+    /// Send a desktop notification when discharging faster than this many percent per minute
+    #[arg(long, value_name = "PERCENT_PER_MIN")]
+    notify_rate: Option<f32>,
+
+    /// Play this sound file with each notification (paplay, afplay or PowerShell)
+    #[arg(long, value_name = "PATH")]
+    notify_sound: Option<PathBuf>,
+
+    /// Ring the terminal bell with each notification
+    #[arg(long, conflicts_with = "notify_sound")]
+    notify_beep: bool,
+
+    /// Skip the desktop notification itself, e.g. to only play the sound
+    #[arg(long)]
+    no_notify_visual: bool,
+
+    /// Silence --notify-sound and --notify-beep
+    #[arg(long)]
+    no_notify_sound: bool,
+
+    /// Run this shell command when charge drops to 20% or below
+    /// (with LITTLE_BAT_CHARGE and LITTLE_BAT_STATE set)
+    #[arg(long, value_name = "CMD")]
+    on_low: Option<String>,
+
+    /// Run this shell command when the battery becomes full
+    #[arg(long, value_name = "CMD")]
+    on_full: Option<String>,
+
+    /// Percent the charge must climb back above a threshold before its alert can fire again
+    #[arg(long, value_name = "N", default_value_t = 2.0)]
+    alert_hysteresis: f32,
+
+    /// Record time spent in each 10% charge bracket per day to this JSON file
+    #[arg(long, value_name = "PATH")]
+    daily_stats: Option<PathBuf>,
+
+    /// Show the average daily time per charge bracket as a bar chart
+    #[arg(long, requires = "daily_stats")]
+    show_daily: bool,
+
+    /// Append a JSON line to PATH whenever an alert condition starts
+    #[arg(long, value_name = "PATH")]
+    alert_file: Option<PathBuf>,
+
+    /// Answer `status`, `charge`, `state` and `quit` commands on this named pipe
+    #[cfg(unix)]
+    #[arg(long, value_name = "PATH")]
+    fifo: Option<PathBuf>,
+
+    /// Show capacity lost relative to the design capacity
+    #[arg(long)]
+    wear: bool,
+
+    /// Show the charge and state in the terminal window title
+    #[arg(long)]
+    window_title: bool,
+
+    /// Window title for --window-title, with `{charge}` and `{state}` filled in
+    #[arg(
+        long,
+        value_name = "STR",
+        default_value = "little-bat: {charge} {state}",
+        requires = "window_title"
+    )]
+    window_title_format: String,
+
+    /// Ease the displayed charge between readings instead of jumping in 1% steps
+    #[arg(long)]
+    smooth_charge: bool,
+
+    /// Weight of each new reading in --smooth-charge, from 0 (frozen) to 1 (no smoothing)
+    #[arg(long, value_name = "ALPHA", default_value_t = 0.3, value_parser = parse_alpha, requires = "smooth_charge")]
+    smooth_alpha: f32,
+
+    /// Unit for energy values such as the --wear capacity lost
+    #[arg(long, value_enum, default_value_t)]
+    energy_unit: EnergyUnit,
+
+    /// Pack voltage used to convert watt-hours to `--energy-unit mah` (11.1 V is a 3-cell Li-ion)
+    #[arg(long, value_name = "V", default_value_t = 11.1, value_parser = parse_voltage)]
+    nominal_voltage: f32,
+
+    /// Don't hint at a calibration cycle after a long discharge below 5%
+    #[arg(long)]
+    no_calibration_hint: bool,
+
+    /// Re-read the battery immediately after the system resumes from suspend (Linux)
+    #[arg(long)]
+    refresh_on_wake: bool,
+
+    /// Seconds to show a failed read before trying the battery again
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    error_display_secs: u64,
+
+    /// Wait for input in crossterm's poll (event) or on a background input thread (sleep)
+    #[arg(long, value_enum, default_value_t)]
+    poll_strategy: PollStrategy,
+
+    /// Minimum milliseconds between redraws, to avoid flicker under rapid input
+    #[arg(long, value_name = "MILLIS", default_value_t = 50)]
+    min_display_interval: u64,
+
+    /// Wait this many seconds before the first battery read, e.g. when started as a service
+    #[arg(long, value_name = "SECS")]
+    startup_delay: Option<u64>,
+
+    /// Try this many times to find a battery at startup, exiting with status 3 if none appears
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    startup_retries: Option<u32>,
+
+    /// Seconds to wait between startup attempts
+    #[arg(long, value_name = "SECS", default_value_t = 1)]
+    startup_retry_interval: u64,
+
+    /// Print the status once in the given format and exit, instead of starting the TUI
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Print the plain status once and exit (same as `--format plain`)
+    #[arg(long, conflicts_with = "format")]
+    once: bool,
+
+    /// Pad the percentage in the plain output to WIDTH columns, e.g. `  7%`
+    #[arg(long, value_name = "WIDTH")]
+    right_align: Option<usize>,
+
+    /// Separate the plain output's fields with spaces up to multiples of N columns
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    tab_stop: Option<usize>,
+
+    /// With --once, print only the charging state and exit 0 (charging/full), 1 (discharging) or 2 (unknown)
+    #[arg(long, requires = "once")]
+    state_only: bool,
+
+    /// With --once, exit with the charge decile: floor(charge / 10), so 0–10 (255 without a
+    /// reading). Exit 0 can mean 0–9% as well as success; --state-only is cleaner for most scripts
+    #[arg(long, requires = "once", conflicts_with_all = ["state_only", "charge_only"])]
+    exit_code_charge: bool,
+
+    /// Print only the charge as a number (e.g. 74) and exit 0 above 50%, 1 above 20%, else 2
+    #[arg(long, conflicts_with = "state_only")]
+    charge_only: bool,
+
+    /// Decimal places for --charge-only
+    #[arg(long, value_name = "N", default_value_t = 0, requires = "charge_only")]
+    precision: usize,
+
+    /// Keep running and write the --format output (plain by default) each time it changes
+    #[arg(long, conflicts_with_all = ["once", "state_only", "charge_only"])]
+    output_on_change: bool,
+
+    /// When full, print nothing in one-shot modes and only `Full ✓` in the TUI
+    #[arg(long)]
+    hide_when_full: bool,
+
+    /// Write the one-shot output to this file (replacing it) instead of stdout
+    #[arg(long, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// Icon before the charge in `--format lualine` while charging (e.g. a Nerd Font glyph)
+    #[arg(long, value_name = "ICON")]
+    lualine_charging_icon: Option<String>,
+
+    /// Icon before the charge in `--format lualine` while discharging
+    #[arg(long, value_name = "ICON")]
+    lualine_discharging_icon: Option<String>,
+
+    /// While running, also replace this file with the `--format json` reading on every refresh
+    #[arg(long, value_name = "PATH")]
+    also_write_json: Option<PathBuf>,
+
+    /// While running, also append a `--format csv` row to this file on every refresh
+    #[arg(long, value_name = "PATH")]
+    also_write_csv: Option<PathBuf>,
+
+    /// While running, also replace this file with the plain status on every refresh
+    #[arg(long, value_name = "PATH")]
+    also_write_plain: Option<PathBuf>,
+
+    /// Don't end the one-shot output with a newline
+    #[arg(long)]
+    no_newline: bool,
+
+    /// Text to print before the one-shot output
+    #[arg(long, value_name = "STR", default_value = "")]
+    prefix: String,
+
+    /// Text to print after the one-shot output
+    #[arg(long, value_name = "STR", default_value = "")]
+    suffix: String,
+
+    /// Check crates.io for a newer release and exit
+    #[arg(long)]
+    version_check: bool,
+
+    /// List every battery the system reports and exit
+    #[arg(long)]
+    list: bool,
+
+    /// Draw N frames as fast as possible, then print the frame rate
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    benchmark: Option<u32>,
+
+    /// Print the widget's lines as plain text to stderr instead of starting the TUI
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Show diagnostics such as the number of failed battery reads
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Display a fixed reading instead of the real battery (e.g. `--simulate 45 discharging`)
+    #[arg(long, num_args = 2, value_names = ["CHARGE", "STATE"])]
+    simulate: Option<Vec<String>>,
+
+    /// Read the battery from this `power_supply` directory instead of the system (e.g. a test fixture)
+    #[arg(long, value_name = "PATH", conflicts_with = "simulate")]
+    sysfs_root: Option<PathBuf>,
+
+    /// Play back readings recorded with `--format json` instead of reading the battery
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["simulate", "sysfs_root"])]
+    replay_file: Option<PathBuf>,
+
+    /// Start `--replay-file` over when it runs out instead of exiting
+    #[arg(long, requires = "replay_file")]
+    replay_loop: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Subcommand)]
+enum Command {
+    /// Check the config file and print the effective settings
+    ValidateConfig,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// `74% Discharging`
+    Plain,
+    /// `timestamp,charge,state,watts,temperature,health`, without a header row
+    Csv,
+    /// Vim/neovim statusline markup using `StatusLineBat*` highlight groups
+    Statusline,
+    /// xmobar `<fc>` color markup
+    Xmobar,
+    /// conky `${color}` markup
+    Conky,
+    /// One JSON object per reading, readable by `--replay-file`
+    Json,
+    /// A Lua table for a neovim lualine component: `{"74%%", guifg="#27AE60"}`
+    Lualine,
+}
+
+/// `--align` for the widget's text lines.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum TextAlign {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+impl From<TextAlign> for Alignment {
+    fn from(align: TextAlign) -> Self {
+        match align {
+            TextAlign::Left => Alignment::Left,
+            TextAlign::Center => Alignment::Center,
+            TextAlign::Right => Alignment::Right,
+        }
+    }
+}
+
+/// `--time-format` for the time remaining.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum TimeFormat {
+    /// `1h 23m`
+    #[default]
+    Hm,
+    /// `1:23`
+    Clock,
+    /// `83`
+    Minutes,
+    /// `4980`
+    Seconds,
+}
+
+/// What `--title-mode` puts above the widget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum TitleMode {
+    #[default]
+    None,
+    /// `bat`
+    Short,
+    /// `little-bat v0.1.0`
+    Full,
+}
+
+/// Unit for displayed energy values.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum EnergyUnit {
+    /// Watt-hours
+    #[default]
+    Wh,
+    /// Milliwatt-hours
+    Mwh,
+    /// Milliamp-hours at the `--nominal-voltage`, as printed on many battery labels
+    Mah,
+}
+
+/// Replacement `--icons` for terminals without emoji, padded to the emoji's width.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum EmojiFallback {
+    /// `+ ` charging, `-` discharging, `=` full, `x` empty
+    Ascii,
+    /// `↯ ` charging, other icons unchanged
+    Unicode,
+}
+
+/// Conditions that make the widget blink with `--blink-state`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum BlinkTrigger {
+    Charging,
+    Discharging,
+    /// Charge at or below 20%
+    Critical,
+    Full,
+    Unknown,
+}
+
+/// Parses a comma-separated list such as `charging,critical`.
+fn parse_blink_states(s: &str) -> Result<BTreeSet<BlinkTrigger>, String> {
+    s.split(',')
+        .map(|name| {
+            BlinkTrigger::from_str(name.trim(), true).map_err(|_| {
+                format!(
+                    "unknown state `{}` (expected charging, discharging, critical, full or unknown)",
+                    name.trim()
+                )
+            })
+        })
+        .collect()
+}
+
+/// A `#rrggbb` hex color or a CSS color name like `limegreen`.
+fn parse_color(s: &str) -> Result<Rgb, String> {
+    styles::parse_named_color(s)
+        .map_or_else(|| parse_hex_color(s), Ok)
+        .map_err(|_| format!("`{s}` is not a hex color like #00ff00 or a color name"))
+}
+
+/// Parses `#rrggbb` (the `#` is optional).
+fn parse_hex_color(s: &str) -> Result<Rgb, String> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok((r, g, b)),
+        _ => Err(format!("`{s}` is not a hex color like #00ff00")),
+    }
+}
+
+fn parse_percent(s: &str) -> Result<f32, String> {
+    let value: f32 = s
+        .trim_end_matches('%')
+        .parse()
+        .map_err(|_| format!("`{s}` is not a percentage"))?;
+    if (0.0..=100.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("`{s}` must be between 0% and 100%"))
+    }
+}
+
+fn parse_alpha(s: &str) -> Result<f32, String> {
+    match s.parse::<f32>() {
+        Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => Ok(alpha),
+        _ => Err(format!("`{s}` must be above 0 and at most 1")),
+    }
+}
+
+fn parse_voltage(s: &str) -> Result<f32, String> {
+    match s.trim_end_matches(['V', 'v']).parse::<f32>() {
+        Ok(volts) if volts.is_finite() && volts > 0.0 => Ok(volts),
+        _ => Err(format!("`{s}` is not a positive voltage")),
+    }
+}
+
+#[derive(Debug)]
+struct StyleError(String);
+
+impl fmt::Display for StyleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unknown style `{}` (expected bold, italic, underline, dim or plain)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for StyleError {}
+
+fn parse_label_style(s: &str) -> Result<Modifier, StyleError> {
+    s.split(',')
+        .map(|name| match name.trim() {
+            "bold" => Ok(Modifier::BOLD),
+            "italic" => Ok(Modifier::ITALIC),
+            "underline" => Ok(Modifier::UNDERLINED),
+            "dim" => Ok(Modifier::DIM),
+            "plain" => Ok(Modifier::empty()),
+            other => Err(StyleError(other.to_string())),
+        })
+        .try_fold(Modifier::empty(), |acc, modifier| Ok(acc | modifier?))
+}
+
+fn parse_simulation(values: &[String]) -> Result<BatteryInfo, String> {
+    let [charge, state] = values else {
+        return Err("expected CHARGE and STATE".to_string());
+    };
+    let state = match State::from_str(state) {
+        Ok(state) => state,
+        Err(_) => return Err(format!("`{state}` is not a battery state")),
+    };
+    Ok(BatteryInfo {
+        charge: parse_percent(charge)?,
+        state,
+        ..BatteryInfo::default()
+    })
+}
+
+/// Position of the `--float` widget, relative to the terminal area.
+struct FloatState {
+    position: Option<(i16, i16)>,
+    velocity: (i16, i16),
+    /// The refresh tick the position was last advanced on.
+    tick: u64,
+}
+
+impl FloatState {
+    /// Places the widget, advancing it one step per refresh tick.
+    fn place(&mut self, area: Rect, size: (u16, u16), tick: u64) -> Rect {
+        let position = match self.position {
+            None => {
+                let centered = centered_rect(area, size.0, size.1);
+                ((centered.x - area.x) as i16, (centered.y - area.y) as i16)
+            }
+            Some(current) if tick != self.tick => {
+                let (position, velocity) = next_float_position(current, self.velocity, area, size);
+                self.velocity = velocity;
+                position
+            }
+            Some(current) => current,
+        };
+        let width = size.0.min(area.width);
+        let height = size.1.min(area.height);
+        // The terminal may have shrunk since the last step, so pull the widget back inside.
+        let position = (
+            position.0.clamp(0, (area.width - width) as i16),
+            position.1.clamp(0, (area.height - height) as i16),
+        );
+        self.position = Some(position);
+        self.tick = tick;
+
+        Rect::new(
+            area.x + position.0 as u16,
+            area.y + position.1 as u16,
+            width,
+            height,
+        )
+    }
+}
+
+struct App {
+    provider: Box<dyn BatteryProvider>,
+    graphic_mode: bool,
+    gauge: bool,
+    bar_style: BarStyle,
+    unicode_bar: bool,
+    hide_percentage: bool,
+    bar_prefix: String,
+    bar_suffix: String,
+    state_prefix: bool,
+    charging_animation: ChargingAnimation,
+    show_label: bool,
+    title: String,
+    title_mode: TitleMode,
+    align: TextAlign,
+    short_state: bool,
+    label_modifier: Modifier,
+    invert: bool,
+    invert_colors: bool,
+    color_mode: ColorMode,
+    inverse_video: bool,
+    show_icons: bool,
+    /// The `--emoji-fallback` set, only when the terminal needs it.
+    icon_fallback: Option<EmojiFallback>,
+    /// Whether RGB colors can be sent as-is rather than approximated from the 256-color palette.
+    true_color: bool,
+    large_digits: bool,
+    fraction: bool,
+    bold_below: Option<f32>,
+    underline_critical: bool,
+    blink_triggers: BTreeSet<BlinkTrigger>,
+    right_align: Option<usize>,
+    tab_stop: Option<usize>,
+    charge_limit: Option<f32>,
+    min_width: u16,
+    max_width: u16,
+    center_offset: (i16, i16),
+    float: Option<FloatState>,
+    transparency: bool,
+    #[cfg(feature = "fun")]
+    rainbow: bool,
+    history: Option<History>,
+    history_view: HistoryView,
+    sparkline_position: SparklinePosition,
+    trend: Trend,
+    show_forecast: bool,
+    time_format: TimeFormat,
+    show_charge_rate: bool,
+    notify_rate: Option<f32>,
+    on_low: Option<String>,
+    on_full: Option<String>,
+    alert_hysteresis: f32,
+    /// Charge-threshold alerts that may fire; each disarms on firing until the
+    /// charge recovers past `alert_hysteresis`.
+    threshold_armed: HashMap<NotifyEvent, bool>,
+    notifier: Notifier,
+    alert_log: Option<AlertLog>,
+    #[cfg(unix)]
+    fifo: Option<Fifo>,
+    /// `--also-write-*` files, each fed its format after every read.
+    fan_out: Vec<(OutputFormat, PathBuf)>,
+    /// `--lualine-charging-icon` and `--lualine-discharging-icon`.
+    lualine_icons: (Option<String>, Option<String>),
+    daily_stats: Option<DailyStats>,
+    show_daily: bool,
+    show_wear: bool,
+    /// `--window-title-format` when `--window-title` is on.
+    window_title: Option<String>,
+    /// The title last sent, so it's only rewritten when it changes.
+    shown_window_title: Option<String>,
+    /// `--smooth-alpha` when `--smooth-charge` is on.
+    smooth_alpha: Option<f32>,
+    /// Exponential moving average of the charge, shown instead of the raw reading
+    /// with `--smooth-charge`. Alerts still use the raw value.
+    smoothed_charge: Option<f32>,
+    energy_unit: EnergyUnit,
+    nominal_voltage: f32,
+    calibration_hint: bool,
+    exit_on_full: bool,
+    exit_on_discharge: bool,
+    battery: Option<BatteryInfo>,
+    /// When the last read failed; the error stays up for `error_display` before a retry.
+    error_shown_since: Option<Instant>,
+    error_display: Duration,
+    /// When `c` last cleared the history, for the `[History cleared]` flash.
+    history_cleared_at: Option<Instant>,
+    /// Failed battery reads this session.
+    read_errors: u64,
+    verbose: bool,
+    hide_when_full: bool,
+    previous_state: Option<State>,
+    discharge_start_time: Option<Instant>,
+    started_at: Instant,
+    timeout: Option<Duration>,
+    last_refresh: Option<Instant>,
+    /// Number of battery refreshes so far, used to pace animations.
+    tick: u64,
+    suspend_detector: Option<SuspendDetector>,
+}
+
+impl App {
+    fn new(args: &Args, provider: Box<dyn BatteryProvider>) -> io::Result<Self> {
+        let mut capabilities = if args.no_term_detect {
+            TermCapabilities::assumed()
+        } else {
+            term::detect_capabilities()
+        };
+        capabilities.true_color |= args.true_color;
+        log::debug!("terminal capabilities: {capabilities:?}");
+        if args.hide_percentage && !args.graphic {
+            log::warn!("--hide-percentage has no effect without --graphic");
+        }
+        Ok(Self {
+            provider,
+            graphic_mode: args.graphic,
+            gauge: args.gauge,
+            bar_style: args.bar_style,
+            unicode_bar: args.unicode_bar && capabilities.unicode,
+            hide_percentage: args.hide_percentage,
+            bar_prefix: args.bar_prefix.clone(),
+            bar_suffix: args.bar_suffix.clone(),
+            state_prefix: args.state_prefix,
+            charging_animation: args.charging_animation,
+            show_label: args.label,
+            title: args.title.clone(),
+            title_mode: args.title_mode,
+            align: args.align,
+            short_state: args.short_state,
+            label_modifier: args.label_style.unwrap_or_default(),
+            invert: args.invert,
+            invert_colors: args.invert_colors,
+            color_mode: match args.two_color.as_deref() {
+                Some(&[high, low]) => ColorMode::TwoColor { high, low },
+                _ if args.color_state_only => ColorMode::ByState,
+                _ => ColorMode::ByCharge,
+            },
+            inverse_video: args.inverse_video,
+            show_icons: args.icons,
+            icon_fallback: args.emoji_fallback.filter(|_| !capabilities.emoji),
+            true_color: capabilities.true_color,
+            large_digits: args.large_digits,
+            fraction: args.fraction,
+            bold_below: args.bold_below,
+            underline_critical: args.underline_critical,
+            blink_triggers: args.blink_state.clone().unwrap_or_default(),
+            right_align: args.right_align,
+            tab_stop: args.tab_stop,
+            charge_limit: args.charge_limit,
+            min_width: args.min_width,
+            max_width: args.max_width,
+            center_offset: match args.center_offset.as_deref() {
+                Some(&[x, y]) => (x, y),
+                _ => (0, 0),
+            },
+            float: args.float_widget.then_some(FloatState {
+                position: None,
+                velocity: (1, 1),
+                tick: 0,
+            }),
+            transparency: args.transparency,
+            #[cfg(feature = "fun")]
+            rainbow: args.rainbow,
+            history: args
+                .history
+                .map(|n| History::new(n.min(args.max_readings) as usize)),
+            sparkline_position: args.sparkline_position,
+            history_view: if args.graph {
+                HistoryView::Chart
+            } else {
+                HistoryView::Sparkline
+            },
+            trend: Trend::default(),
+            show_forecast: args.forecast,
+            time_format: args.time_format,
+            show_charge_rate: args.charge_rate,
+            notify_rate: args.notify_rate,
+            on_low: args.on_low.clone(),
+            on_full: args.on_full.clone(),
+            alert_hysteresis: args.alert_hysteresis,
+            threshold_armed: HashMap::new(),
+            notifier: Notifier::new(
+                !args.no_notify_visual,
+                match (&args.notify_sound, args.notify_beep) {
+                    _ if args.no_notify_sound => None,
+                    (Some(path), _) => Some(Sound::File(path.clone())),
+                    (None, true) => Some(Sound::Beep),
+                    (None, false) => None,
+                },
+            ),
+            alert_log: args.alert_file.as_deref().map(AlertLog::open).transpose()?,
+            #[cfg(unix)]
+            fifo: args.fifo.as_deref().map(Fifo::open).transpose()?,
+            lualine_icons: (
+                args.lualine_charging_icon.clone(),
+                args.lualine_discharging_icon.clone(),
+            ),
+            fan_out: [
+                (OutputFormat::Json, &args.also_write_json),
+                (OutputFormat::Csv, &args.also_write_csv),
+                (OutputFormat::Plain, &args.also_write_plain),
+            ]
+            .into_iter()
+            .filter_map(|(format, path)| Some((format, path.clone()?)))
+            .collect(),
+            daily_stats: args
+                .daily_stats
+                .as_deref()
+                .map(DailyStats::load)
+                .transpose()?,
+            show_daily: args.show_daily,
+            show_wear: args.wear,
+            window_title: args.window_title.then(|| args.window_title_format.clone()),
+            shown_window_title: None,
+            smooth_alpha: args.smooth_charge.then_some(args.smooth_alpha),
+            smoothed_charge: None,
+            energy_unit: args.energy_unit,
+            nominal_voltage: args.nominal_voltage,
+            calibration_hint: !args.no_calibration_hint,
+            exit_on_full: args.exit_on_full,
+            exit_on_discharge: args.exit_on_discharge,
+            battery: None,
+            error_shown_since: None,
+            error_display: Duration::from_secs(args.error_display_secs),
+            history_cleared_at: None,
+            read_errors: 0,
+            verbose: args.verbose,
+            hide_when_full: args.hide_when_full,
+            previous_state: None,
+            discharge_start_time: None,
+            started_at: Instant::now(),
+            timeout: args.timeout.map(Duration::from_secs),
+            last_refresh: None,
+            tick: 0,
+            suspend_detector: args.refresh_on_wake.then(SuspendDetector::new).flatten(),
+        })
+    }
+
+    fn timed_out(&self) -> bool {
+        self.timeout
+            .is_some_and(|timeout| self.started_at.elapsed() >= timeout)
+    }
+
+    /// How long to wait for input before the next refresh, capped by the timeout.
+    fn poll_interval(&self) -> Duration {
+        let mut interval = match self.last_refresh {
+            Some(last) => self.refresh_interval().saturating_sub(last.elapsed()),
+            None => Duration::ZERO,
+        };
+        if let Some(timeout) = self.timeout {
+            interval = interval.min(timeout.saturating_sub(self.started_at.elapsed()));
+        }
+        if self.suspend_detector.is_some() {
+            interval = interval.min(WAKE_CHECK_INTERVAL);
+        }
+        #[cfg(feature = "fun")]
+        if self.rainbow {
+            interval = interval.min(ANIMATION_FRAME);
+        }
+        if self.active_animation() != ChargingAnimation::None {
+            interval = interval.min(ANIMATION_FRAME);
+        }
+        if let Some(cleared) = self.history_cleared_at {
+            interval = interval.min(FLASH_DURATION.saturating_sub(cleared.elapsed()));
+        }
+        interval
+    }
+
+    /// Forgets the readings behind `--history` and the trend, starting both afresh
+    /// from the next reading.
+    fn clear_history(&mut self) {
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+        self.trend = Trend::default();
+        self.history_cleared_at = Some(Instant::now());
+    }
+
+    fn history_flash_visible(&self) -> bool {
+        self.history_cleared_at
+            .is_some_and(|cleared| cleared.elapsed() < FLASH_DURATION)
+    }
+
+    /// Time between reads, stretched to `--error-display-secs` while a read error is up.
+    fn refresh_interval(&self) -> Duration {
+        match self.error_shown_since {
+            Some(_) => self.error_display.max(REFRESH_INTERVAL),
+            None => REFRESH_INTERVAL,
+        }
+    }
+
+    /// Whether the battery should be re-read: the refresh interval has passed or
+    /// the system just resumed from suspend.
+    fn refresh_due(&mut self) -> bool {
+        let resumed = self
+            .suspend_detector
+            .as_mut()
+            .is_some_and(SuspendDetector::check_resumed);
+        resumed
+            || self
+                .last_refresh
+                .is_none_or(|last| last.elapsed() >= self.refresh_interval())
+    }
+
+    fn refresh(&mut self) {
+        self.last_refresh = Some(Instant::now());
+        self.tick += 1;
+        if let Some(info) = self.battery {
+            self.previous_state = Some(info.state);
+        }
+        let reading = self.provider.battery_info().unwrap_or_else(|err| {
+            self.read_errors += 1;
+            log::debug!("battery read failed: {err}");
+            None
+        });
+        // A failed read stays on screen until a retry succeeds.
+        self.error_shown_since = match reading {
+            Some(_) => None,
+            None => Some(Instant::now()),
+        };
+        self.battery = reading;
+
+        if let Some(charge) = reading.and_then(|info| sanitize_charge(info.charge)) {
+            if let Some(alpha) = self.smooth_alpha {
+                let previous = self.smoothed_charge.unwrap_or(charge);
+                self.smoothed_charge = Some(alpha * charge + (1.0 - alpha) * previous);
+            }
+            self.trend.push(charge, Instant::now());
+            if let Some(history) = &mut self.history {
+                history.push(charge);
+            }
+            if let Some(stats) = &mut self.daily_stats {
+                stats.record(charge);
+            }
+        }
+
+        self.check_alerts();
+
+        #[cfg(unix)]
+        if let Some(fifo) = &self.fifo {
+            fifo.publish(fifo::Status {
+                status: plain_status(self),
+                charge: self
+                    .battery
+                    .and_then(|info| sanitize_charge(info.charge))
+                    .map_or_else(String::new, |charge| format!("{charge:.0}")),
+                state: self
+                    .battery
+                    .map_or_else(String::new, |info| state_text(info.state)),
+            });
+        }
+        self.write_fan_out();
+
+        let discharging = self
+            .battery
+            .is_some_and(|info| info.state == State::Discharging);
+        if !discharging {
+            self.discharge_start_time = None;
+        } else if self.discharge_start_time.is_none() {
+            self.discharge_start_time = Some(Instant::now());
+        }
+    }
+
+    /// Writes the current reading to each `--also-write-*` file. CSV rows accumulate;
+    /// the other formats only ever hold the latest reading.
+    fn write_fan_out(&self) {
+        for (format, path) in &self.fan_out {
+            let line = format_output(self, *format) + "\n";
+            let written = if *format == OutputFormat::Csv {
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| file.write_all(line.as_bytes()))
+            } else {
+                fs::write(path, line)
+            };
+            if let Err(err) = written {
+                log::debug!("writing {} failed: {err}", path.display());
+            }
+        }
+    }
+
+    /// Sends the `--window-title` if the reading changed it.
+    fn update_window_title(&mut self) -> io::Result<()> {
+        let Some(format) = &self.window_title else {
+            return Ok(());
+        };
+        let (charge, state) = match self.battery {
+            Some(info) => (
+                sanitize_charge(info.charge)
+                    .map_or_else(String::new, |c| output::charge_text(c, self.fraction)),
+                state_label(info.state, self),
+            ),
+            None => (String::new(), "No battery".to_string()),
+        };
+        let title = output::fill_template(format, &charge, &state);
+        if self.shown_window_title.as_ref() != Some(&title) {
+            set_window_title(&title)?;
+            self.shown_window_title = Some(title);
+        }
+        Ok(())
+    }
+
+    /// The charge to draw: the `--smooth-charge` average when enabled, else the reading.
+    fn displayed_charge(&self) -> Option<f32> {
+        let raw = self.battery.and_then(|info| sanitize_charge(info.charge))?;
+        Some(self.smoothed_charge.unwrap_or(raw))
+    }
+
+    /// Sends notifications for alert conditions in the latest reading.
+    fn check_alerts(&mut self) {
+        let Some(info) = self.battery else {
+            return;
+        };
+        let fast_drain = match (self.notify_rate, self.trend.charge_rate()) {
+            (Some(threshold), Some(rate)) if -rate > threshold => Some((threshold, rate)),
+            _ => None,
+        };
+
+        if let Some((threshold, rate)) = fast_drain {
+            self.notifier.notify(
+                NotifyEvent::HighDischargeRate,
+                "Battery draining fast",
+                &format!(
+                    "Discharging at {:.1}%/min (threshold {threshold:.1}%/min)",
+                    -rate
+                ),
+            );
+        }
+
+        let charge = sanitize_charge(info.charge);
+        let low = charge.is_some_and(|c| c <= CRITICAL_THRESHOLD);
+        let low_armed = self
+            .threshold_armed
+            .entry(NotifyEvent::LowBattery)
+            .or_insert(true);
+        if charge.is_some_and(|c| c > CRITICAL_THRESHOLD + self.alert_hysteresis) {
+            *low_armed = true;
+        }
+        let fire_low = low && *low_armed;
+        if fire_low {
+            *low_armed = false;
+        }
+        // As with --exit-on-full, starting on a full battery doesn't count.
+        let became_full = info.state == State::Full
+            && self
+                .previous_state
+                .is_some_and(|previous| previous != State::Full);
+        let hook_env = [
+            ("LITTLE_BAT_CHARGE", format!("{:.0}", info.charge)),
+            ("LITTLE_BAT_STATE", state_text(info.state)),
+        ];
+        if let Some(command) = &self.on_low
+            && fire_low
+        {
+            self.notifier
+                .run_hook(NotifyEvent::LowBattery, command, &hook_env);
+        }
+        if let Some(command) = &self.on_full
+            && became_full
+        {
+            self.notifier
+                .run_hook(NotifyEvent::Full, command, &hook_env);
+        }
+
+        if let Some(log) = &mut self.alert_log {
+            log.update(AlertKind::LowBattery, low, &info);
+            log.update(AlertKind::HighDischargeRate, fast_drain.is_some(), &info);
+            if self
+                .previous_state
+                .is_some_and(|previous| previous != info.state)
+            {
+                log.append(AlertKind::StateChange, &info);
+            }
+        }
+    }
+
+    /// `color` as the terminal can show it: RGB becomes the nearest 256-color entry
+    /// without true color support.
+    fn palette(&self, color: Color) -> Color {
+        match color {
+            Color::Rgb(r, g, b) if !self.true_color => styles::indexed((r, g, b)),
+            _ => color,
+        }
+    }
+
+    /// The `--charging-animation` to draw right now: only the bar animates, and only while charging.
+    fn active_animation(&self) -> ChargingAnimation {
+        let charging = self
+            .battery
+            .is_some_and(|info| info.state == State::Charging);
+        if self.graphic_mode && charging {
+            self.charging_animation
+        } else {
+            ChargingAnimation::None
+        }
+    }
+
+    /// With `--hide-when-full`, whether only a minimal indicator (or nothing) should show.
+    fn hidden_when_full(&self) -> bool {
+        self.hide_when_full && self.battery.is_some_and(|info| info.state == State::Full)
+    }
+
+    /// Whether the current reading matches any `--blink-state` trigger.
+    fn should_blink(&self) -> bool {
+        let Some(info) = self.battery else {
+            return false;
+        };
+        self.blink_triggers.iter().any(|trigger| match trigger {
+            BlinkTrigger::Charging => info.state == State::Charging,
+            BlinkTrigger::Discharging => info.state == State::Discharging,
+            BlinkTrigger::Critical => info.charge <= CRITICAL_THRESHOLD,
+            BlinkTrigger::Full => info.state == State::Full,
+            BlinkTrigger::Unknown => info.state == State::Unknown,
+        })
+    }
+
+    /// Animation frames elapsed since startup.
+    fn animation_tick(&self) -> u64 {
+        (self.started_at.elapsed().as_millis() / ANIMATION_FRAME.as_millis()) as u64
+    }
+
+    /// A sustained discharge down to a few percent usually means a calibration cycle.
+    fn calibrating(&self) -> bool {
+        self.calibration_hint
+            && self.battery.is_some_and(|info| info.charge < 5.0)
+            && self
+                .discharge_start_time
+                .is_some_and(|start| start.elapsed() > CALIBRATION_DISCHARGE)
+    }
+
+    /// Starts a new `--wrap` round: the timeout restarts, and the `--exit-on-*`
+    /// conditions need a fresh state change.
+    fn rearm(&mut self) {
+        self.started_at = Instant::now();
+        self.previous_state = self.battery.map(|info| info.state);
+    }
+
+    /// Returns the exit code once an `--exit-on-*` condition is met.
+    fn exit_condition(&self) -> Option<ExitCode> {
+        let state = self.battery?.state;
+        // Only a change into Full counts, so launching on a full battery doesn't exit.
+        if self.exit_on_full
+            && state == State::Full
+            && self
+                .previous_state
+                .is_some_and(|previous| previous != State::Full)
+        {
+            return Some(ExitCode::SUCCESS);
+        }
+        if self.exit_on_discharge
+            && state == State::Discharging
+            && matches!(self.previous_state, Some(State::Charging | State::Full))
+        {
+            return Some(ExitCode::from(2));
+        }
+        None
+    }
+}
+
+fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    env_logger::init();
+    let mut args = Args::parse();
+    if args.version_check {
+        return Ok(version_check::run());
+    }
+    if args.list {
+        return list_batteries();
+    }
+    let loaded = config::load(args.config.as_deref());
+    if let Some(Command::ValidateConfig) = args.command {
+        return Ok(match loaded {
+            Ok(loaded) => config::validate(&args, &loaded),
+            Err(err) => {
+                println!("error: {err}");
+                ExitCode::FAILURE
+            }
+        });
+    }
+    if let Err(err) = loaded.and_then(|loaded| loaded.apply(&mut args)) {
+        eprintln!("little-bat: {err}");
+        return Ok(ExitCode::FAILURE);
+    }
+
+    let provider: Box<dyn BatteryProvider> = match &args.simulate {
+        Some(values) => match parse_simulation(values) {
+            Ok(info) => Box::new(SimulatedBatteryProvider::new(info)),
+            Err(err) => Args::command()
+                .error(clap::error::ErrorKind::ValueValidation, err)
+                .exit(),
+        },
+        None if let Some(path) = &args.replay_file => {
+            match ReplayBatteryProvider::load(path, args.replay_loop) {
+                Ok(provider) => Box::new(provider),
+                Err(err) => {
+                    eprintln!("little-bat: {err}");
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+        }
+        None if let Some(root) = &args.sysfs_root => {
+            Box::new(SysfsBatteryProvider::new(root.clone()))
+        }
+        None => {
+            let provider = SystemBatteryProvider::new()?;
+            if args.startup_delay.is_some() || args.startup_retries.is_some() {
+                thread::sleep(Duration::from_secs(args.startup_delay.unwrap_or(0)));
+                let attempts = args.startup_retries.unwrap_or(1);
+                let interval = Duration::from_secs(args.startup_retry_interval);
+                if !provider.wait_for_battery(attempts, interval) {
+                    eprintln!("little-bat: no battery found after {attempts} attempt(s)");
+                    return Ok(ExitCode::from(EXIT_NO_BATTERY));
+                }
+            }
+            Box::new(provider)
+        }
+    };
+    let mut app = match App::new(&args, provider) {
+        Ok(app) => app,
+        Err(err) => {
+            eprintln!("little-bat: {err}");
+            return Ok(ExitCode::FAILURE);
+        }
+    };
+
+    if let Some(path) = &args.once_if_changed {
+        app.refresh();
+        return Ok(once_if_changed(&app, path)?);
+    }
+
+    if args.state_only {
+        app.refresh();
+        let state = app.battery.map_or(State::Unknown, |info| info.state);
+        print_once(&state_label(state, &app), &args)?;
+        return Ok(state_exit_code(state));
+    }
+
+    if args.charge_only {
+        app.refresh();
+        let Some(charge) = app.battery.and_then(|info| sanitize_charge(info.charge)) else {
+            eprintln!("little-bat: {}", plain_status(&app));
+            return Ok(ExitCode::from(EXIT_NO_BATTERY));
+        };
+        print_once(
+            &format!("{charge:.precision$}", precision = args.precision),
+            &args,
+        )?;
+        return Ok(charge_exit_code(charge));
+    }
+
+    if args.dry_run {
+        app.refresh();
+        for line in content_lines(&app) {
+            let text: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            eprintln!("{text}");
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(threshold) = args.watch_threshold {
+        return Ok(watch_threshold(&mut app, threshold, args.above));
+    }
+
+    let one_shot = args.format.or(args.once.then_some(OutputFormat::Plain));
+    // A single read can't see a state change, so `--exit-on-*` keeps polling instead.
+    let waits_for_exit = args.exit_on_full || args.exit_on_discharge;
+    if args.output_on_change || (one_shot.is_some() && waits_for_exit) {
+        return Ok(output_on_change(&mut app, &args)?);
+    }
+
+    if let Some(format) = one_shot {
+        app.refresh();
+        let code = if args.exit_code_charge {
+            app.battery
+                .and_then(|info| sanitize_charge(info.charge))
+                .map_or(ExitCode::from(u8::MAX), |charge| {
+                    ExitCode::from((charge / 10.0).floor() as u8)
+                })
+        } else {
+            ExitCode::SUCCESS
+        };
+        if !app.hidden_when_full() {
+            print_once(&one_shot_output(&app, &args, format), &args)?;
+        }
+        return Ok(code);
+    }
+
+    if args.watch_state_change {
+        return Ok(watch_state_change(&mut app));
+    }
+
+    if let Some(frames) = args.benchmark {
+        return benchmark(&mut app, frames);
+    }
+
+    let mut guard = TerminalGuard::new();
+    let waiter = Waiter::new(args.poll_strategy, REFRESH_INTERVAL);
+    let min_display_interval = Duration::from_millis(args.min_display_interval);
+    loop {
+        match run(&mut guard.terminal, &mut app, &waiter, min_display_interval)? {
+            Exit::Condition(_) if args.wrap => app.rearm(),
+            Exit::Condition(code) => return Ok(code),
+            Exit::Quit => return Ok(ExitCode::SUCCESS),
+        }
+    }
+}
+
+/// Owns the TUI terminal and restores it when dropped, even during unwinding.
+struct TerminalGuard {
+    terminal: DefaultTerminal,
+}
+
+impl TerminalGuard {
+    fn new() -> Self {
+        // Leave raw mode before the panic message is printed, not after.
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            ratatui::restore();
+            default_hook(info);
+        }));
+        Self {
+            terminal: ratatui::init(),
+        }
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        ratatui::restore();
+    }
+}
+
+/// Prints the status and stores it in `path`, unless it matches what was stored last time.
+fn once_if_changed(app: &App, path: &Path) -> std::io::Result<ExitCode> {
+    let status = plain_status(app);
+    let last = fs::read_to_string(path).unwrap_or_default();
+    if last == status {
+        return Ok(ExitCode::FAILURE);
+    }
+    println!("{status}");
+    fs::write(path, &status)?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Keeps reading the battery, writing the output only when it differs from the last one.
+fn output_on_change(app: &mut App, args: &Args) -> io::Result<ExitCode> {
+    let format = args.format.unwrap_or(OutputFormat::Plain);
+    let mut last_hash = None;
+    loop {
+        app.refresh();
+        let output = one_shot_output(app, args, format);
+        let mut hasher = DefaultHasher::new();
+        output.hash(&mut hasher);
+        let hash = Some(hasher.finish());
+        if hash != last_hash {
+            print_once(&output, args)?;
+            last_hash = hash;
+        }
+        if let Some(code) = app.exit_condition() {
+            return Ok(code);
+        }
+        if app.timed_out() || app.provider.exhausted() {
+            return Ok(ExitCode::SUCCESS);
+        }
+        thread::sleep(REFRESH_INTERVAL);
+    }
+}
+
+/// Polls silently until charge crosses `threshold` in the requested direction.
+fn watch_threshold(app: &mut App, threshold: f32, above: bool) -> ExitCode {
+    loop {
+        app.refresh();
+        if let Some(charge) = app.battery.and_then(|info| sanitize_charge(info.charge)) {
+            let crossed = if above {
+                charge > threshold
+            } else {
+                charge < threshold
+            };
+            if crossed {
+                return ExitCode::SUCCESS;
+            }
+        }
+        if app.timed_out() {
+            return ExitCode::FAILURE;
+        }
+        thread::sleep(app.poll_interval());
+    }
 }
 
-struct App {
-    manager: Manager,
-    graphic_mode: bool,
-    show_label: bool,
+/// Polls until the state differs from the first reading, then prints the new one.
+fn watch_state_change(app: &mut App) -> ExitCode {
+    let mut initial = None;
+    loop {
+        app.refresh();
+        if let Some(state) = app.battery.map(|info| info.state) {
+            match initial {
+                None => initial = Some(state),
+                Some(initial) if initial != state => {
+                    println!("{}", state_label(state, app));
+                    return ExitCode::SUCCESS;
+                }
+                Some(_) => {}
+            }
+        }
+        if app.timed_out() {
+            return ExitCode::FAILURE;
+        }
+        thread::sleep(app.poll_interval());
+    }
 }
 
-impl App {
-    fn new(graphic_mode: bool, show_label: bool) -> Result<Self, battery::Error> {
-        Ok(Self {
-            manager: Manager::new()?,
-            graphic_mode,
-            show_label,
-        })
+/// Prints `--list`: one row per system battery, numbered from 0 in the order the
+/// display picks from.
+fn list_batteries() -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let batteries = SystemBatteryProvider::new()?.list()?;
+    if batteries.is_empty() {
+        eprintln!("little-bat: no batteries found");
+        if cfg!(target_os = "linux") {
+            eprintln!("Check that /sys/class/power_supply/ has a BAT* entry");
+        }
+        return Ok(ExitCode::from(EXIT_NO_BATTERY));
     }
+    let header = ["INDEX", "VENDOR", "MODEL", "TECHNOLOGY", "CHARGE"].map(String::from);
+    let rows = batteries.iter().enumerate().map(|(index, battery)| {
+        vec![
+            index.to_string(),
+            battery.vendor.clone(),
+            battery.model.clone(),
+            battery.technology.clone(),
+            format!("{:.0}%", battery.charge),
+        ]
+    });
+    let rows: Vec<Vec<String>> = std::iter::once(header.to_vec()).chain(rows).collect();
+    print!("{}", output::table(&rows));
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Below this, `--benchmark` suggests the terminal is the bottleneck.
+const BENCHMARK_MIN_FPS: f64 = 30.0;
 
-    fn get_battery_info(&self) -> Option<(f32, State)> {
-        self.manager
-            .batteries()
-            .ok()?
-            .next()?
-            .ok()
-            .map(|b| (b.state_of_charge().get::<percent>(), b.state()))
+/// Draws `frames` frames back to back from a single reading and reports the frame rate.
+fn benchmark(app: &mut App, frames: u32) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    app.refresh();
+    let elapsed = {
+        let mut guard = TerminalGuard::new();
+        let start = Instant::now();
+        for _ in 0..frames {
+            let mut rendered = Ok(());
+            guard.terminal.draw(|frame| rendered = render(frame, app))?;
+            rendered?;
+        }
+        start.elapsed()
+    };
+
+    let fps = f64::from(frames) / elapsed.as_secs_f64();
+    println!(
+        "Rendered {frames} frames in {:.1} ms ({fps:.0} FPS)",
+        elapsed.as_secs_f64() * 1000.0
+    );
+    if fps < BENCHMARK_MIN_FPS {
+        eprintln!(
+            "warning: below {BENCHMARK_MIN_FPS:.0} FPS; the terminal emulator may be the bottleneck"
+        );
     }
+    Ok(ExitCode::SUCCESS)
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Args::parse();
-    let terminal = ratatui::init();
-    let app = App::new(args.graphic, args.label)?;
-    let result = run(terminal, app);
-    ratatui::restore();
-    result
+/// Why the TUI loop stopped.
+enum Exit {
+    /// The user pressed `q`/Esc, sent `quit` to the `--fifo`, or the `--replay-file` ran out.
+    Quit,
+    /// An `--exit-on-*` condition or the `--timeout`, which `--wrap` restarts from.
+    Condition(ExitCode),
 }
 
-fn run(mut terminal: DefaultTerminal, app: App) -> Result<(), Box<dyn std::error::Error>> {
+fn run(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    waiter: &Waiter,
+    min_display_interval: Duration,
+) -> Result<Exit, Box<dyn std::error::Error>> {
+    let mut last_draw: Option<Instant> = None;
     loop {
-        terminal.draw(|frame| render(frame, &app))?;
-
-        if event::poll(Duration::from_secs(1))? {
-            if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press
-                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
-                {
-                    break;
-                }
+        if app.refresh_due() {
+            app.refresh();
+            app.update_window_title()?;
+        }
+        if let Some(code) = app.exit_condition() {
+            return Ok(Exit::Condition(code));
+        }
+        if app.timed_out() {
+            return Ok(Exit::Condition(ExitCode::SUCCESS));
+        }
+        #[cfg(unix)]
+        if app.fifo.as_ref().is_some_and(Fifo::quit_requested) {
+            return Ok(Exit::Quit);
+        }
+
+        // A skipped redraw happens as soon as the interval is up.
+        let redraw_in = last_draw
+            .map(|last| min_display_interval.saturating_sub(last.elapsed()))
+            .filter(|wait| !wait.is_zero());
+        // The final replayed reading is drawn before exiting, whatever the interval.
+        let exhausted = app.provider.exhausted();
+        if redraw_in.is_none() || exhausted {
+            let mut rendered = Ok(());
+            terminal.draw(|frame| rendered = render(frame, app))?;
+            rendered?;
+            last_draw = Some(Instant::now());
+        }
+        if exhausted {
+            return Ok(Exit::Quit);
+        }
+
+        let timeout = app.poll_interval().min(redraw_in.unwrap_or(Duration::MAX));
+        if let Some(key) = waiter.next_key(timeout)?
+            && key.kind == KeyEventKind::Press
+        {
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(Exit::Quit),
+                KeyCode::Char('g') => app.history_view = app.history_view.toggled(),
+                KeyCode::Char('c') => app.clear_history(),
+                _ => {}
             }
         }
     }
-    Ok(())
 }
 
-fn render(frame: &mut Frame, app: &App) {
+/// Sets the terminal window title with OSC 2, alongside ratatui's drawing.
+fn set_window_title(title: &str) -> io::Result<()> {
+    // A stray BEL or ESC would end the sequence early.
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    let mut stdout = io::stdout().lock();
+    write!(stdout, "\x1B]2;{title}\x07")?;
+    stdout.flush()
+}
+
+#[derive(Debug)]
+enum RenderError {
+    /// The content is too large to lay out in a terminal.
+    ContentTooLarge { width: usize, height: usize },
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::ContentTooLarge { width, height } => {
+                write!(
+                    f,
+                    "content of {width}x{height} cells is too large to render"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+fn render(frame: &mut Frame, app: &mut App) -> Result<(), RenderError> {
     let area = frame.area();
 
-    let content = match app.get_battery_info() {
-        Some((charge, state)) => {
-            if app.graphic_mode {
-                render_graphic(charge, state, app.show_label)
+    let content = content_lines(app);
+
+    if app.hidden_when_full() {
+        let rect = centered_rect(area, content_width(&content) as u16 + 2, 1);
+        let rect = offset_rect(rect, area, app.center_offset);
+        draw_widget(
+            Paragraph::new(content).alignment(app.align.into()),
+            rect,
+            frame.buffer_mut(),
+            app.transparency,
+        );
+        if app.inverse_video {
+            reverse_cells(rect, frame.buffer_mut(), app.transparency);
+        }
+        return Ok(());
+    }
+
+    let height = content.len();
+    let width = content_width(&content) + 2;
+    let (Ok(width), Ok(content_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(RenderError::ContentTooLarge { width, height });
+    };
+    let beside = app.sparkline_position.beside();
+    let (history_width, history_height) = match &app.history {
+        Some(history) => app.history_view.size(history.capacity(), beside),
+        None => (0, 0),
+    };
+    let (daily_width, daily_height) = if app.show_daily {
+        (daily::CHART_WIDTH, daily::CHART_HEIGHT)
+    } else {
+        (0, 0)
+    };
+    let gauge_charge = app.displayed_charge().filter(|_| app.gauge);
+    let (gauge_width, gauge_height) = match gauge_charge {
+        Some(_) => (GAUGE_MIN_WIDTH, 1),
+        None => (0, 0),
+    };
+    // Beside the reading, the history gets its own column instead of a row.
+    let (stacked_width, stacked_height) = if beside {
+        (0, 0)
+    } else {
+        (history_width, history_height)
+    };
+    let main_width = width.max(stacked_width).max(daily_width).max(gauge_width);
+    let main_height = content_height
+        .saturating_add(gauge_height)
+        .saturating_add(stacked_height)
+        .saturating_add(daily_height);
+    let (width, height) = if beside && app.history.is_some() {
+        (
+            main_width.saturating_add(1).saturating_add(history_width),
+            main_height.max(history_height),
+        )
+    } else {
+        (main_width, main_height)
+    };
+    let centered = layout::constrained_rect(
+        app.min_width,
+        app.max_width,
+        0,
+        u16::MAX,
+        area,
+        width,
+        height,
+    );
+    let placed = match &mut app.float {
+        Some(float) => float.place(area, (centered.width, centered.height), app.tick),
+        None => offset_rect(centered, area, app.center_offset),
+    };
+    let (main_area, side_area) = if beside && app.history.is_some() {
+        let [left, _, right] = Layout::horizontal([
+            Constraint::Length(if app.sparkline_position == SparklinePosition::Left {
+                history_width
+            } else {
+                main_width
+            }),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+        ])
+        .areas(placed);
+        if app.sparkline_position == SparklinePosition::Left {
+            (right, left)
+        } else {
+            (left, right)
+        }
+    } else {
+        (placed, Rect::default())
+    };
+    let above = app.sparkline_position == SparklinePosition::Above;
+    let [above_area, gauge_area, content_area, below_area, daily_area] = Layout::vertical([
+        Constraint::Length(if above { stacked_height } else { 0 }),
+        Constraint::Length(gauge_height),
+        Constraint::Length(content_height),
+        Constraint::Length(if above { 0 } else { stacked_height }),
+        Constraint::Length(daily_height),
+    ])
+    .areas(main_area);
+    let history_area = match app.sparkline_position {
+        SparklinePosition::Above => above_area,
+        SparklinePosition::Below => below_area,
+        SparklinePosition::Left | SparklinePosition::Right => side_area,
+    };
+
+    if let Some(charge) = gauge_charge {
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(widget_color(charge, app)))
+            .percent(charge.round() as u16);
+        draw_widget(gauge, gauge_area, frame.buffer_mut(), app.transparency);
+    }
+
+    let widget = Paragraph::new(content).alignment(app.align.into());
+    draw_widget(widget, content_area, frame.buffer_mut(), app.transparency);
+
+    // Without --verbose, flag that some of what's shown may be a stale reading.
+    if !app.verbose && app.read_errors > 0 {
+        let corner = layout::corner_rect(Corner::TopRight, content_area, 1, 1);
+        let marker = Span::styled("!", Style::default().fg(Color::Red));
+        draw_widget(marker, corner, frame.buffer_mut(), app.transparency);
+    }
+
+    if let Some(history) = &app.history {
+        let color = app
+            .displayed_charge()
+            .map_or(Color::DarkGray, |charge| widget_color(charge, app));
+        history::render(
+            history,
+            app.history_view,
+            beside,
+            color,
+            history_area,
+            frame.buffer_mut(),
+            app.transparency,
+        );
+    }
+
+    if app.show_daily
+        && let Some(stats) = &app.daily_stats
+    {
+        stats.render(daily_area, frame.buffer_mut(), app.transparency);
+    }
+
+    // After everything is drawn, so the gauge, history and daily chart are swapped too.
+    if app.inverse_video {
+        reverse_cells(placed, frame.buffer_mut(), app.transparency);
+    }
+    Ok(())
+}
+
+/// The widget's text lines: the reading in the selected mode, then any extra info lines.
+fn content_lines(app: &App) -> Vec<Line<'static>> {
+    if app.hidden_when_full() {
+        return vec![Line::from(Span::styled(
+            "Full ✓",
+            Style::default().fg(Color::Green),
+        ))];
+    }
+
+    let mut content = match app.battery.map(|info| (app.displayed_charge(), info.state)) {
+        Some((Some(charge), state)) => {
+            if app.gauge {
+                gauge_caption(state, app)
+            } else if app.graphic_mode {
+                render_graphic(charge, state, app)
             } else {
-                render_percentage(charge, state, app.show_label)
+                render_percentage(charge, state, app)
             }
         }
+        Some((None, _)) => vec![Line::from("Invalid reading")],
         None => vec![Line::from("No battery found")],
     };
 
-    let height = content.len() as u16;
-    let width = content.iter().map(|l| l.width()).max().unwrap_or(20) as u16;
-    let centered = centered_rect(area, width + 2, height);
+    if app.show_forecast
+        && let Some(line) = app
+            .battery
+            .and_then(|info| forecast_line(info.state, &app.trend, app.time_format))
+    {
+        content.push(line);
+    }
 
-    let widget = Paragraph::new(content).centered();
-    frame.render_widget(widget, centered);
-}
+    if app.show_charge_rate
+        && let Some(rate) = app.trend.charge_rate()
+    {
+        content.push(Line::from(Span::styled(
+            format!("Rate: {rate:+.1}%/min"),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
 
-fn render_percentage(charge: f32, state: State, show_label: bool) -> Vec<Line<'static>> {
-    let color = charge_color(charge);
-    let mut lines = Vec::new();
+    if app.show_wear
+        && let Some((percent, lost)) = app.battery.as_ref().and_then(wear)
+    {
+        let (lost, unit) = convert_energy(lost, app.energy_unit, app.nominal_voltage);
+        // Milli-units are already fine-grained enough without decimals.
+        let precision = usize::from(app.energy_unit == EnergyUnit::Wh);
+        content.push(Line::from(Span::styled(
+            format!("Wear: {percent:.0}% ({lost:.precision$} {unit} lost)"),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+
+    if app.verbose {
+        content.push(Line::from(Span::styled(
+            format!("Read errors: {}", app.read_errors),
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
 
-    if show_label {
-        lines.push(Line::from("Battery"));
+    if app.calibrating() {
+        content.push(Line::from(Span::styled(
+            "Extended discharge detected",
+            Style::default().fg(Color::Magenta),
+        )));
     }
 
-    lines.push(Line::from(Span::styled(
-        format!("{:.0}%", charge),
-        Style::default().fg(color),
-    )));
+    let header = match app.title_mode {
+        TitleMode::None => None,
+        TitleMode::Short => Some("bat".to_string()),
+        TitleMode::Full => Some(format!("little-bat v{}", env!("CARGO_PKG_VERSION"))),
+    };
+    if let Some(header) = header {
+        content.insert(
+            0,
+            Line::from(Span::styled(
+                header,
+                Style::default().add_modifier(Modifier::BOLD),
+            )),
+        );
+    }
 
-    if show_label {
-        lines.push(Line::from(Span::styled(
-            state_text(state),
+    if app.history_flash_visible() {
+        content.push(Line::from(Span::styled(
+            "[History cleared]",
             Style::default().fg(Color::DarkGray),
         )));
     }
 
-    lines
+    content
 }
 
-fn render_graphic(charge: f32, state: State, show_label: bool) -> Vec<Line<'static>> {
-    let color = charge_color(charge);
-    let filled = (charge / 10.0).round() as usize;
-    let empty = 10 - filled;
+fn content_width(content: &[Line]) -> usize {
+    content.iter().map(|l| l.width()).max().unwrap_or(20)
+}
+
+fn draw_widget(widget: impl Widget, area: Rect, buf: &mut Buffer, transparent: bool) {
+    if transparent {
+        render_transparent(widget, area, buf);
+    } else {
+        widget.render(area, buf);
+    }
+}
+
+/// Applies `--inverse-video` to `area`, skipping blank cells under `--transparency`.
+fn reverse_cells(area: Rect, buf: &mut Buffer, transparent: bool) {
+    for position in area.intersection(buf.area).positions() {
+        let cell = &mut buf[position];
+        if !(transparent && cell.symbol() == " ") {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+    }
+}
 
-    let bar = format!(
-        "[{}{}]",
-        "█".repeat(filled),
-        "░".repeat(empty)
+/// Renders `widget` off-screen and copies only its visible glyphs, without backgrounds.
+fn render_transparent(widget: impl Widget, area: Rect, buf: &mut Buffer) {
+    let mut scratch = Buffer::empty(area);
+    widget.render(area, &mut scratch);
+    for position in area.positions() {
+        let cell = &scratch[position];
+        if cell.symbol() == " " {
+            continue;
+        }
+        let target = &mut buf[position];
+        target.set_symbol(cell.symbol());
+        target.fg = cell.fg;
+        target.modifier = cell.modifier;
+    }
+}
+
+/// The lines under a `--gauge`; the gauge itself is drawn separately by [`render`].
+fn gauge_caption(state: State, app: &App) -> Vec<Line<'static>> {
+    if !app.show_label {
+        return Vec::new();
+    }
+    let state_style = apply_state_modifier(
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(app.label_modifier),
+        state,
     );
+    vec![Line::from(Span::styled(
+        state_label(state, app),
+        state_style,
+    ))]
+}
+
+fn render_percentage(charge: f32, state: State, app: &App) -> Vec<Line<'static>> {
+    let color = widget_color(charge, app);
+    let mut emphasis = emphasis_modifier(charge, app.bold_below, app.underline_critical);
+    if app.should_blink() {
+        emphasis |= Modifier::SLOW_BLINK;
+    }
+    let style = apply_state_modifier(Style::default().fg(color), state).add_modifier(emphasis);
+    let label_style =
+        apply_state_modifier(Style::default().add_modifier(app.label_modifier), state)
+            .add_modifier(emphasis);
+    let state_style = apply_state_modifier(
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(app.label_modifier),
+        state,
+    )
+    .add_modifier(emphasis);
+    let mut lines = Vec::new();
+
+    if app.show_label {
+        lines.push(Line::from(Span::styled(app.title.clone(), label_style)));
+    }
+
+    lines.push(with_icon(
+        Span::styled(format_percentage(charge, app), style),
+        state,
+        color,
+        app,
+    ));
+
+    if app.show_label {
+        lines.push(Line::from(Span::styled(
+            state_label(state, app),
+            state_style,
+        )));
+    }
 
+    lines
+}
+
+fn render_graphic(charge: f32, state: State, app: &App) -> Vec<Line<'static>> {
+    let color = widget_color(charge, app);
+    let mut emphasis = emphasis_modifier(charge, app.bold_below, app.underline_critical);
+    if app.should_blink() {
+        emphasis |= Modifier::SLOW_BLINK;
+    }
+    let style = apply_state_modifier(Style::default().fg(color), state).add_modifier(emphasis);
+    let label_style =
+        apply_state_modifier(Style::default().add_modifier(app.label_modifier), state)
+            .add_modifier(emphasis);
+    let state_style = apply_state_modifier(
+        Style::default()
+            .fg(Color::DarkGray)
+            .add_modifier(app.label_modifier),
+        state,
+    )
+    .add_modifier(emphasis);
     let mut lines = Vec::new();
 
-    if show_label {
-        lines.push(Line::from("Battery"));
+    if app.show_label {
+        lines.push(Line::from(Span::styled(app.title.clone(), label_style)));
     }
 
-    lines.push(Line::from(Span::styled(bar, Style::default().fg(color))));
+    let mut bar = bar_spans(charge, state, style, app);
+    if app.state_prefix {
+        let arrow_style =
+            apply_state_modifier(Style::default().fg(state_icon_color(state, color)), state);
+        bar.insert(0, Span::styled(state_arrow(state), arrow_style));
+    }
+    lines.push(with_icon(bar, state, color, app));
 
-    lines.push(Line::from(Span::styled(
-        format!("{:.0}%", charge),
-        Style::default().fg(color),
-    )));
+    if !app.hide_percentage {
+        lines.push(Line::from(Span::styled(
+            format_percentage(charge, app),
+            style,
+        )));
+    }
 
-    if show_label {
+    if app.show_label {
         lines.push(Line::from(Span::styled(
-            state_text(state),
-            Style::default().fg(Color::DarkGray),
+            state_label(state, app),
+            state_style,
         )));
     }
 
     lines
 }
 
-fn charge_color(charge: f32) -> Color {
-    if charge > 50.0 {
+/// Builds the bar in the `--bar-style` glyphs between `--bar-prefix` and `--bar-suffix`,
+/// with the `--charge-limit` marker when set.
+fn bar_spans(charge: f32, state: State, style: Style, app: &App) -> Vec<Span<'static>> {
+    let filled = ((charge / 10.0).round() as usize).min(10);
+    let empty = 10 - filled;
+    let (full_cell, empty_cell) = (app.bar_style.filled(), app.bar_style.empty());
+
+    let tick = app.animation_tick();
+    let (style, sweep) = match app.active_animation() {
+        ChargingAnimation::None => (style, None),
+        ChargingAnimation::Pulse => {
+            let base = style.fg.map_or((229, 229, 229), styles::rgb);
+            (
+                style.fg(app.palette(styles::pulse_color(base, tick, PULSE_PERIOD))),
+                None,
+            )
+        }
+        ChargingAnimation::Sweep => (style, Some(tick as usize)),
+    };
+
+    if app.unicode_bar {
+        // The background shows through the unfilled part of the edge cell.
+        return vec![
+            Span::styled(app.bar_prefix.clone(), style),
+            Span::styled(styles::smooth_bar(charge, 10), style.bg(Color::DarkGray)),
+            Span::styled(app.bar_suffix.clone(), style),
+        ];
+    }
+
+    let mut spans = match app.charge_limit {
+        None => {
+            let mut spans = filled_cells(full_cell, filled, style, sweep);
+            spans.push(Span::styled(empty_cell.repeat(empty), style));
+            spans
+        }
+        Some(limit) => {
+            // Cells past the limit are only reachable when the firmware limit is bypassed.
+            let limit = (limit / 10.0).round() as usize;
+            let below = filled.min(limit);
+            let excess = filled - below;
+            let excess_style = apply_state_modifier(Style::default().fg(Color::Magenta), state);
+            let (left_empty, right_empty) = (limit - below, empty - (limit - below));
+
+            let mut spans = filled_cells(full_cell, below, style, sweep);
+            spans.extend([
+                Span::styled(empty_cell.repeat(left_empty), style),
+                Span::styled(if app.invert { "◀" } else { "▶" }, style),
+                Span::styled(full_cell.repeat(excess), excess_style),
+                Span::styled(empty_cell.repeat(right_empty), style),
+            ]);
+            spans
+        }
+    };
+    if app.invert {
+        spans.reverse();
+    }
+    spans.insert(0, Span::styled(app.bar_prefix.clone(), style));
+    spans.push(Span::styled(app.bar_suffix.clone(), style));
+    spans
+}
+
+/// A run of `count` filled cells, with the `--charging-animation sweep` highlight on
+/// cell `sweep % count` when given.
+fn filled_cells(
+    cell: &'static str,
+    count: usize,
+    style: Style,
+    sweep: Option<usize>,
+) -> Vec<Span<'static>> {
+    match sweep {
+        Some(step) if count > 0 => {
+            let lit = step % count;
+            vec![
+                Span::styled(cell.repeat(lit), style),
+                Span::styled(cell, style.fg(Color::White)),
+                Span::styled(cell.repeat(count - lit - 1), style),
+            ]
+        }
+        _ => vec![Span::styled(cell.repeat(count), style)],
+    }
+}
+
+fn format_output(app: &App, format: OutputFormat) -> String {
+    let charge = app.battery.and_then(|info| sanitize_charge(info.charge));
+    let markup = |markup: fn(&str, &str, Color) -> String| match (charge, app.battery) {
+        (Some(charge), Some(info)) => markup(
+            &output::charge_text(charge, app.fraction),
+            &state_label(info.state, app),
+            charge_color(charge, app.invert_colors),
+        ),
+        _ => String::new(),
+    };
+    match format {
+        OutputFormat::Plain => plain_status(app),
+        OutputFormat::Csv => output::csv_row(app.battery.as_ref(), SystemTime::now()),
+        OutputFormat::Statusline => charge.map_or_else(String::new, |charge| {
+            output::statusline(
+                &output::charge_text(charge, app.fraction),
+                charge_color(charge, app.invert_colors),
+            )
+        }),
+        OutputFormat::Xmobar => markup(output::xmobar),
+        OutputFormat::Conky => markup(output::conky),
+        OutputFormat::Lualine => match (charge, app.battery) {
+            (Some(charge), Some(info)) => output::lualine(
+                &output::charge_text(charge, app.fraction),
+                match info.state {
+                    State::Charging => app.lualine_icons.0.as_deref(),
+                    State::Discharging => app.lualine_icons.1.as_deref(),
+                    _ => None,
+                },
+                charge_color(charge, app.invert_colors),
+            ),
+            _ => String::new(),
+        },
+        OutputFormat::Json => app.battery.map_or_else(String::new, |info| {
+            serde_json::to_string(&info).expect("readings always serialize")
+        }),
+    }
+}
+
+/// The `--format` output wrapped in `--prefix` and `--suffix`, or nothing when
+/// `--hide-when-full` applies.
+fn one_shot_output(app: &App, args: &Args, format: OutputFormat) -> String {
+    if app.hidden_when_full() {
+        return String::new();
+    }
+    format!(
+        "{}{}{}",
+        args.prefix,
+        format_output(app, format),
+        args.suffix
+    )
+}
+
+/// Writes one-shot output to stdout, or to the `--output` file.
+fn print_once(output: &str, args: &Args) -> io::Result<()> {
+    let mut writer: Box<dyn Write> = match &args.output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    };
+    if args.no_newline {
+        write!(writer, "{output}")?;
+    } else {
+        writeln!(writer, "{output}")?;
+    }
+    writer.flush()
+}
+
+/// Exit status for `--state-only`, so scripts can branch on it directly.
+fn state_exit_code(state: State) -> ExitCode {
+    match state {
+        State::Charging | State::Full => ExitCode::SUCCESS,
+        State::Discharging | State::Empty => ExitCode::from(1),
+        _ => ExitCode::from(2),
+    }
+}
+
+/// Exit status for `--charge-only`: 0 above the warning threshold, 1 above critical, else 2.
+fn charge_exit_code(charge: f32) -> ExitCode {
+    if charge > WARNING_THRESHOLD {
+        ExitCode::SUCCESS
+    } else if charge > CRITICAL_THRESHOLD {
+        ExitCode::from(1)
+    } else {
+        ExitCode::from(2)
+    }
+}
+
+/// Single-line status used by the non-TUI output modes, e.g. `74% Discharging`.
+fn plain_status(app: &App) -> String {
+    match app
+        .battery
+        .map(|info| (sanitize_charge(info.charge), info.state))
+    {
+        Some((Some(charge), state)) => {
+            let charge = format_percentage(charge, app);
+            let charge = match app.right_align {
+                Some(width) => output::right_align(&charge, width),
+                None => charge,
+            };
+            match app.tab_stop {
+                Some(n) => format!(
+                    "{}{}",
+                    output::align_to_tab(&charge, n),
+                    state_label(state, app)
+                ),
+                None => format!("{charge} {}", state_label(state, app)),
+            }
+        }
+        Some((None, _)) => "Invalid reading".to_string(),
+        None => "No battery found".to_string(),
+    }
+}
+
+fn format_percentage(charge: f32, app: &App) -> String {
+    let text = output::charge_text(charge, app.fraction);
+    if app.large_digits {
+        to_large_digits(&text)
+    } else {
+        text
+    }
+}
+
+/// Maps ASCII digits and `%` to their fullwidth forms (U+FF10–U+FF19, U+FF05).
+fn to_large_digits(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '0'..='9' => char::from_u32(0xFF10 + (c as u32 - '0' as u32)).unwrap_or(c),
+            '%' => '％',
+            _ => c,
+        })
+        .collect()
+}
+
+/// `Est. empty: 2h 15m` while discharging, `Est. full: 47m` while charging.
+fn forecast_line(state: State, trend: &Trend, format: TimeFormat) -> Option<Line<'static>> {
+    let (label, target) = match state {
+        State::Discharging => ("empty", 0.0),
+        State::Charging => ("full", 100.0),
+        _ => return None,
+    };
+    let remaining = trend.forecast(target)?;
+    Some(Line::from(Span::styled(
+        format!("Est. {label}: {}", format_duration(remaining, format)),
+        Style::default().fg(Color::DarkGray),
+    )))
+}
+
+/// Formats a duration in the `--time-format`: `2h 15m` (just `47m` under an hour),
+/// `2:15`, `135` minutes or `8100` seconds.
+fn format_duration(duration: Duration, format: TimeFormat) -> String {
+    let minutes = duration.as_secs() / 60;
+    match format {
+        TimeFormat::Hm => match (minutes / 60, minutes % 60) {
+            (0, m) => format!("{m}m"),
+            (h, m) => format!("{h}h {m}m"),
+        },
+        TimeFormat::Clock => format!("{}:{:02}", minutes / 60, minutes % 60),
+        TimeFormat::Minutes => minutes.to_string(),
+        TimeFormat::Seconds => duration.as_secs().to_string(),
+    }
+}
+
+/// Capacity lost since manufacture, as `(percent, watt-hours)`.
+fn wear(info: &BatteryInfo) -> Option<(f32, f32)> {
+    let design = info.energy_full_design.filter(|&wh| wh > 0.0)?;
+    let lost = design - info.energy_full?;
+    Some((lost / design * 100.0, lost))
+}
+
+/// `wh` watt-hours in `unit`, with the unit's label. Milliamp-hours assume a pack
+/// at `voltage` volts.
+fn convert_energy(wh: f32, unit: EnergyUnit, voltage: f32) -> (f32, &'static str) {
+    match unit {
+        EnergyUnit::Wh => (wh, "Wh"),
+        EnergyUnit::Mwh => (wh * 1000.0, "mWh"),
+        EnergyUnit::Mah => (wh * 1000.0 / voltage, "mAh"),
+    }
+}
+
+/// Rejects NaN, infinite and out-of-range readings from buggy drivers.
+///
+/// Values within a small tolerance of the valid range are clamped to 0–100.
+fn sanitize_charge(raw: f32) -> Option<f32> {
+    if raw.is_finite() && (-0.1..=100.1).contains(&raw) {
+        Some(raw.clamp(0.0, 100.0))
+    } else {
+        None
+    }
+}
+
+/// The color for the battery display, honouring the color mode flags.
+fn widget_color(charge: f32, app: &App) -> Color {
+    #[cfg(feature = "fun")]
+    if app.rainbow || charge.round() == 69.0 {
+        return app.palette(rainbow_color(app.started_at.elapsed()));
+    }
+    let color = match app.color_mode {
+        ColorMode::ByCharge => charge_color(charge, app.invert_colors),
+        ColorMode::TwoColor { high, low } => {
+            let t = charge / 100.0;
+            styles::two_color_lerp(high, low, if app.invert_colors { 1.0 - t } else { t })
+        }
+        ColorMode::ByState => state_color(app.battery.map_or(State::Unknown, |info| info.state)),
+    };
+    app.palette(color)
+}
+
+/// One full trip around the hue circle every ten seconds.
+#[cfg(feature = "fun")]
+fn rainbow_color(elapsed: Duration) -> Color {
+    let hue = (elapsed.as_secs_f32() % 10.0) * 36.0;
+    let (r, g, b) = hsv_to_rgb(hue, 1.0, 1.0);
+    Color::Rgb(r, g, b)
+}
+
+/// Converts a hue in degrees plus saturation and value in 0–1 to RGB.
+#[cfg(feature = "fun")]
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> (u8, u8, u8) {
+    let c = value * saturation;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = value - c;
+    let (r, g, b) = match hue as u32 {
+        0..60 => (c, x, 0.0),
+        60..120 => (x, c, 0.0),
+        120..180 => (0.0, c, x),
+        180..240 => (0.0, x, c),
+        240..300 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let byte = |v: f32| ((v + m) * 255.0).round() as u8;
+    (byte(r), byte(g), byte(b))
+}
+
+fn charge_color(charge: f32, inverted: bool) -> Color {
+    let charge = if inverted { 100.0 - charge } else { charge };
+    if charge > WARNING_THRESHOLD {
         Color::Green
-    } else if charge > 20.0 {
+    } else if charge > CRITICAL_THRESHOLD {
         Color::Yellow
     } else {
         Color::Red
     }
 }
 
+fn state_color(state: State) -> Color {
+    match state {
+        State::Charging => Color::Cyan,
+        State::Discharging => Color::White,
+        State::Full => Color::Green,
+        State::Empty => Color::Red,
+        _ => Color::Gray,
+    }
+}
+
+/// Extra emphasis for a low battery: bold below the `--bold-below` threshold, and
+/// underlined at critical charge with `--underline-critical`, so it isn't signalled
+/// by color alone.
+fn emphasis_modifier(charge: f32, threshold: Option<f32>, underline_critical: bool) -> Modifier {
+    let mut modifier = Modifier::empty();
+    if threshold.is_some_and(|threshold| charge < threshold) {
+        modifier |= Modifier::BOLD;
+    }
+    if underline_critical && charge <= CRITICAL_THRESHOLD {
+        modifier |= Modifier::UNDERLINED;
+    }
+    modifier
+}
+
+/// Dims the whole widget when the state is unknown, since the reading is unreliable.
+fn apply_state_modifier(style: Style, state: State) -> Style {
+    match state {
+        State::Charging | State::Discharging | State::Full | State::Empty => style,
+        _ => style.fg(Color::DarkGray).add_modifier(Modifier::DIM),
+    }
+}
+
+/// The state as shown to the user: `state_text`, or a single letter with `--short-state`.
+fn state_label(state: State, app: &App) -> String {
+    if !app.short_state {
+        return state_text(state);
+    }
+    match state {
+        State::Charging => "C",
+        State::Discharging => "D",
+        State::Full => "F",
+        State::Empty => "E",
+        _ => "?",
+    }
+    .to_string()
+}
+
 fn state_text(state: State) -> String {
     match state {
         State::Charging => "Charging".to_string(),
@@ -172,10 +2516,139 @@ fn state_text(state: State) -> String {
     }
 }
 
-fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
-    let vertical = Layout::vertical([Constraint::Length(height)]).flex(Flex::Center);
-    let horizontal = Layout::horizontal([Constraint::Length(width)]).flex(Flex::Center);
-    let [area] = vertical.areas(area);
-    let [area] = horizontal.areas(area);
-    area
+fn state_icon(state: State, fallback: Option<EmojiFallback>) -> &'static str {
+    match (state, fallback) {
+        (State::Charging, None) => "⚡",
+        // ⚡ takes two columns, so its replacements do too.
+        (State::Charging, Some(EmojiFallback::Ascii)) => "+ ",
+        (State::Charging, Some(EmojiFallback::Unicode)) => "↯ ",
+        (State::Discharging, Some(EmojiFallback::Ascii)) => "-",
+        (State::Discharging, _) => "↓",
+        (State::Full, Some(EmojiFallback::Ascii)) => "=",
+        (State::Full, _) => "✓",
+        (State::Empty, Some(EmojiFallback::Ascii)) => "x",
+        (State::Empty, _) => "✗",
+        _ => "?",
+    }
+}
+
+/// The `--state-prefix` indicator in front of the bar.
+fn state_arrow(state: State) -> &'static str {
+    match state {
+        State::Charging => "↑",
+        State::Discharging => "↓",
+        State::Full | State::Empty => "=",
+        _ => "?",
+    }
+}
+
+fn state_icon_color(state: State, charge_color: Color) -> Color {
+    match state {
+        State::Charging => Color::Cyan,
+        State::Discharging => charge_color,
+        State::Full => Color::Green,
+        State::Empty => Color::Red,
+        _ => Color::DarkGray,
+    }
+}
+
+/// Builds a line from `spans`, prefixed with the state icon when `--icons` is set.
+fn with_icon(
+    spans: impl Into<Line<'static>>,
+    state: State,
+    color: Color,
+    app: &App,
+) -> Line<'static> {
+    let mut line = spans.into();
+    if app.show_icons {
+        let icon = Span::styled(
+            format!("{} ", state_icon(state, app.icon_fallback)),
+            apply_state_modifier(Style::default().fg(state_icon_color(state, color)), state),
+        );
+        line.spans.insert(0, icon);
+    }
+    line
+}
+
+/// Advances a floating widget by `velocity`, bouncing off the edges of `area`.
+///
+/// Positions are relative to the top-left corner of `area`.
+fn next_float_position(
+    current: (i16, i16),
+    velocity: (i16, i16),
+    area: Rect,
+    widget_size: (u16, u16),
+) -> ((i16, i16), (i16, i16)) {
+    let step = |pos: i16, vel: i16, span: u16, size: u16| {
+        let max = span.saturating_sub(size) as i16;
+        let vel = if pos + vel < 0 || pos + vel > max {
+            -vel
+        } else {
+            vel
+        };
+        ((pos + vel).clamp(0, max), vel)
+    };
+    let (x, vx) = step(current.0, velocity.0, area.width, widget_size.0);
+    let (y, vy) = step(current.1, velocity.1, area.height, widget_size.1);
+    ((x, y), (vx, vy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_charge_rejects_invalid_readings() {
+        assert_eq!(sanitize_charge(f32::NAN), None);
+        assert_eq!(sanitize_charge(f32::INFINITY), None);
+        assert_eq!(sanitize_charge(f32::NEG_INFINITY), None);
+        assert_eq!(sanitize_charge(-5.0), None);
+        assert_eq!(sanitize_charge(150.0), None);
+    }
+
+    #[test]
+    fn sanitize_charge_clamps_values_near_the_bounds() {
+        assert_eq!(sanitize_charge(-0.05), Some(0.0));
+        assert_eq!(sanitize_charge(100.05), Some(100.0));
+        assert_eq!(sanitize_charge(74.3), Some(74.3));
+    }
+
+    #[test]
+    fn float_stays_inside_a_shrunk_terminal() {
+        let mut float = FloatState {
+            position: None,
+            velocity: (1, 1),
+            tick: 0,
+        };
+        float.place(Rect::new(0, 0, 80, 24), (20, 3), 0);
+        float.position = Some((51, 18));
+        let area = Rect::new(0, 0, 30, 8);
+        let rect = float.place(area, (20, 3), 0);
+        assert_eq!(rect.intersection(area), rect);
+    }
+
+    #[test]
+    fn format_duration_in_each_time_format() {
+        let remaining = Duration::from_secs(83 * 60 + 20);
+        assert_eq!(format_duration(remaining, TimeFormat::Hm), "1h 23m");
+        assert_eq!(format_duration(remaining, TimeFormat::Clock), "1:23");
+        assert_eq!(format_duration(remaining, TimeFormat::Minutes), "83");
+        assert_eq!(format_duration(remaining, TimeFormat::Seconds), "5000");
+        assert_eq!(
+            format_duration(Duration::from_secs(47 * 60), TimeFormat::Hm),
+            "47m"
+        );
+    }
+
+    #[test]
+    fn transparent_inverse_video_skips_blank_cells() {
+        let mut buf = Buffer::with_lines(["a b"]);
+        reverse_cells(buf.area, &mut buf, true);
+        let reversed: Vec<_> = buf
+            .content()
+            .iter()
+            .map(|cell| cell.modifier.contains(Modifier::REVERSED))
+            .collect();
+        assert_eq!(reversed, [true, false, true]);
+    }
 }
@@ -0,0 +1,52 @@
+//! Tokio versions of the headless API, behind the `async` feature.
+//!
+//! `battery::Manager` can't move between threads, so each read opens its own
+//! manager on tokio's blocking pool.
+
+use crate::provider::{BatteryInfo, BatteryProvider, SystemBatteryProvider};
+use std::io;
+use std::time::Duration;
+use tokio::task;
+use tokio::time::{self, MissedTickBehavior};
+
+/// Reads the system battery without blocking the async runtime.
+/// Returns one entry per battery found.
+pub async fn get_battery_info_async() -> Result<Vec<BatteryInfo>, battery::Error> {
+    task::spawn_blocking(|| SystemBatteryProvider::new()?.battery_info())
+        .await
+        .map_err(io::Error::other)?
+        .map(|info| info.into_iter().collect())
+}
+
+/// Like [`crate::run_headless`] for the system battery, but on a tokio interval.
+/// Stops on Ctrl-C or, on Unix, SIGTERM.
+pub async fn run_async(
+    interval: Duration,
+    mut on_update: impl FnMut(Vec<BatteryInfo>),
+) -> Result<(), battery::Error> {
+    let mut ticks = time::interval(interval);
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    loop {
+        tokio::select! {
+            _ = ticks.tick() => on_update(get_battery_info_async().await?),
+            result = &mut shutdown => return result.map_err(battery::Error::from),
+        }
+    }
+}
+
+/// Resolves on the first Ctrl-C or SIGTERM.
+async fn shutdown_signal() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        let mut term = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = term.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
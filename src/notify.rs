@@ -0,0 +1,130 @@
+//! Desktop notifications and `--on-*` hook commands, rate-limited per event kind.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Minimum time between two notifications of the same kind.
+const NOTIFY_COOLDOWN: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NotifyEvent {
+    HighDischargeRate,
+    LowBattery,
+    Full,
+}
+
+/// Sound played alongside a notification.
+#[derive(Clone, Debug)]
+pub enum Sound {
+    /// The terminal bell.
+    Beep,
+    /// A sound file, played with the platform's command-line player.
+    File(PathBuf),
+}
+
+pub struct Notifier {
+    last_sent: HashMap<NotifyEvent, Instant>,
+    /// Whether to show desktop notifications; sounds play either way.
+    visual: bool,
+    sound: Option<Sound>,
+}
+
+impl Notifier {
+    pub fn new(visual: bool, sound: Option<Sound>) -> Self {
+        Self {
+            last_sent: HashMap::new(),
+            visual,
+            sound,
+        }
+    }
+
+    /// Sends a notification, and plays the sound if one is set, unless one of the same
+    /// kind was sent within the cooldown.
+    pub fn notify(&mut self, event: NotifyEvent, summary: &str, body: &str) {
+        if !self.cooled_down(event) {
+            return;
+        }
+        if self.visual {
+            send_desktop_notification(summary, body);
+        }
+        if let Some(sound) = &self.sound {
+            play_sound(sound);
+        }
+    }
+
+    /// Runs `command` with `sh -c` unless it ran for the same kind within the cooldown.
+    ///
+    /// `env` is added to the command's environment; failures are ignored.
+    pub fn run_hook(&mut self, event: NotifyEvent, command: &str, env: &[(&str, String)]) {
+        if !self.cooled_down(event) {
+            return;
+        }
+        let mut hook = Command::new("sh");
+        hook.arg("-c").arg(command).envs(env.iter().cloned());
+        thread::spawn(move || hook.status());
+    }
+
+    /// Records an event, returning false if the previous one of its kind is too recent.
+    fn cooled_down(&mut self, event: NotifyEvent) -> bool {
+        let now = Instant::now();
+        if self
+            .last_sent
+            .get(&event)
+            .is_some_and(|last| now.duration_since(*last) < NOTIFY_COOLDOWN)
+        {
+            return false;
+        }
+        self.last_sent.insert(event, now);
+        true
+    }
+}
+
+/// Hands the notification to the platform's notifier without blocking the display.
+///
+/// Failures (e.g. `notify-send` not installed) are ignored.
+fn send_desktop_notification(summary: &str, body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!("display notification {:?} with title {:?}", body, summary);
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=little-bat").arg(summary).arg(body);
+        command
+    };
+    thread::spawn(move || command.status());
+}
+
+/// Plays `sound` without blocking the display. Failures (e.g. no `paplay`) are ignored.
+fn play_sound(sound: &Sound) {
+    let path = match sound {
+        Sound::Beep => {
+            let mut stdout = io::stdout().lock();
+            let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+            return;
+        }
+        Sound::File(path) => path,
+    };
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("afplay");
+        command.arg(path);
+        command
+    } else if cfg!(windows) {
+        let path = path.display().to_string().replace('\'', "''");
+        let mut command = Command::new("powershell");
+        command.arg("-c").arg(format!(
+            "(New-Object Media.SoundPlayer '{path}').PlaySync()"
+        ));
+        command
+    } else {
+        let mut command = Command::new("paplay");
+        command.arg(path);
+        command
+    };
+    thread::spawn(move || command.status());
+}
@@ -0,0 +1,203 @@
+//! Machine-readable output formats for the one-shot modes.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ratatui::style::Color;
+
+use crate::{sanitize_charge, state_text};
+use little_bat::provider::BatteryInfo;
+
+/// One CSV row: `timestamp,charge,state,watts,temperature,health`.
+///
+/// Missing readings are left empty so every row has the same fields.
+pub fn csv_row(info: Option<&BatteryInfo>, now: SystemTime) -> String {
+    let fields = match info {
+        Some(info) => [
+            optional(sanitize_charge(info.charge), 1),
+            csv_field(&state_text(info.state)),
+            optional(info.energy_rate, 1),
+            optional(info.temperature, 1),
+            optional(info.health, 0),
+        ],
+        None => Default::default(),
+    };
+    format!("{},{}", format_timestamp(now), fields.join(","))
+}
+
+/// Vim statusline markup, e.g. `%#StatusLineBatGreen#74%%%#StatusLine#`.
+///
+/// The highlight groups must be defined in the user's vim config, for example:
+///
+/// ```vim
+/// highlight StatusLineBatGreen  ctermfg=green  guifg=#27AE60
+/// highlight StatusLineBatYellow ctermfg=yellow guifg=#F1C40F
+/// highlight StatusLineBatRed    ctermfg=red    guifg=#E74C3C
+/// set statusline+=%{%trim(system('little-bat\ --format\ statusline'))%}
+/// ```
+pub fn statusline(charge: &str, color: Color) -> String {
+    let group = match color {
+        Color::Green => "Green",
+        Color::Yellow => "Yellow",
+        _ => "Red",
+    };
+    let charge = charge.replace('%', "%%");
+    format!("%#StatusLineBat{group}#{charge}%#StatusLine#")
+}
+
+/// xmobar markup, e.g. `<fc=#27AE60>74%</fc> Discharging`.
+///
+/// Run it from `.xmobarrc` as a command and reference its alias in the template:
+///
+/// ```haskell
+/// , commands = [ Run Com "little-bat" ["--format", "xmobar"] "battery" 600 ]
+/// , template = "... }{ %battery% "
+/// ```
+pub fn xmobar(charge: &str, state: &str, color: Color) -> String {
+    format!("<fc={}>{charge}</fc> {state}", hex_color(color))
+}
+
+/// conky markup, e.g. `${color #27AE60}74%${color} Discharging`.
+///
+/// Use `execpi` rather than `exec` so conky parses the color markup, here every 60 seconds:
+///
+/// ```text
+/// conky.text = [[
+/// Battery: ${execpi 60 little-bat --format conky}
+/// ]]
+/// ```
+pub fn conky(charge: &str, state: &str, color: Color) -> String {
+    format!("${{color {}}}{charge}${{color}} {state}", hex_color(color))
+}
+
+/// A Lua table literal for a lualine component, e.g. `{"74%%", guifg="#27AE60"}`, with
+/// `icon` (typically a Nerd Font glyph) in front of the charge when given.
+///
+/// ```lua
+/// sections = { lualine_x = { function() return vim.fn.system("little-bat --format lualine") end } }
+/// ```
+pub fn lualine(charge: &str, icon: Option<&str>, color: Color) -> String {
+    let text = match icon {
+        Some(icon) => format!("{icon} {charge}"),
+        None => charge.to_string(),
+    };
+    // Lualine text ends up in the statusline, where `%` must be doubled.
+    let text = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%");
+    format!("{{\"{text}\", guifg=\"{}\"}}", hex_color(color))
+}
+
+/// The hex colors matching the vim highlight groups suggested for [`statusline`].
+fn hex_color(color: Color) -> &'static str {
+    match color {
+        Color::Green => "#27AE60",
+        Color::Yellow => "#F1C40F",
+        _ => "#E74C3C",
+    }
+}
+
+/// Charge as `74%`, or as `0.740` with `--fraction`.
+pub fn charge_text(charge: f32, fraction: bool) -> String {
+    if fraction {
+        format!("{:.3}", charge / 100.0)
+    } else {
+        format!("{charge:.0}%")
+    }
+}
+
+/// Fills the `{charge}` and `{state}` tokens in `template`, e.g.
+/// `little-bat: {charge} {state}` becomes `little-bat: 74% Discharging`.
+pub fn fill_template(template: &str, charge: &str, state: &str) -> String {
+    template
+        .replace("{charge}", charge)
+        .replace("{state}", state)
+}
+
+/// Pads `s` on the left to `width` columns, e.g. `  7%`.
+pub fn right_align(s: &str, width: usize) -> String {
+    format!("{s:>width$}")
+}
+
+/// Pads `s` with spaces to the next multiple of `n` columns, like a tab would, so
+/// `74%` with `n = 8` becomes `74%     `. There is always at least one space.
+pub fn align_to_tab(s: &str, n: usize) -> String {
+    let width = s.chars().count();
+    let stop = (width / n + 1) * n;
+    format!("{s:<stop$}")
+}
+
+/// Lays out `rows` in columns two spaces apart, each as wide as its widest cell.
+/// The last column is not padded.
+pub fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|row| row.get(i))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{cell:<width$}"))
+                .collect();
+            cells.join("  ").trim_end().to_string() + "\n"
+        })
+        .collect()
+}
+
+/// Quotes a field if it contains a comma, quote or newline.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn optional(value: Option<f32>, precision: usize) -> String {
+    value.map_or_else(String::new, |v| format!("{v:.precision$}"))
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp, e.g. `2024-01-15T14:32:07Z`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = unix_secs(time);
+    let rem = secs % 86_400;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        format_date(time),
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// Formats the UTC date of `time`, e.g. `2024-01-15`.
+pub fn format_date(time: SystemTime) -> String {
+    let (year, month, day) = civil_from_days((unix_secs(time) / 86_400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) date in the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
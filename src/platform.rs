@@ -0,0 +1,4 @@
+//! Operating-system specific additions to the cross-platform `battery` readings.
+
+#[cfg(target_os = "macos")]
+pub(crate) mod macos;
@@ -0,0 +1,109 @@
+//! Extra `AppleSmartBattery` properties read straight from IOKit, for fields the
+//! `battery` crate leaves empty on macOS.
+
+use crate::provider::BatteryInfo;
+use battery::State;
+use core_foundation::base::{CFType, TCFType, kCFAllocatorDefault};
+use core_foundation::boolean::CFBoolean;
+use core_foundation::dictionary::{CFDictionary, CFMutableDictionaryRef};
+use core_foundation::number::CFNumber;
+use core_foundation::string::CFString;
+use io_kit_sys::{
+    IOObjectRelease, IORegistryEntryCreateCFProperties, IOServiceGetMatchingService,
+    IOServiceMatching, kIOMasterPortDefault,
+};
+use std::ptr;
+
+/// The `AppleSmartBattery` properties little-bat knows how to use.
+struct SmartBattery {
+    /// mAh, uncorrected by the battery's own calibration.
+    raw_current_capacity: Option<f32>,
+    raw_max_capacity: Option<f32>,
+    design_capacity: Option<f32>,
+    /// mV, to convert the mAh capacities to watt-hours.
+    voltage: Option<f32>,
+    is_charging: Option<bool>,
+    external_connected: Option<bool>,
+    /// Hundredths of a degree Celsius.
+    temperature: Option<f32>,
+}
+
+/// Fills in whatever `info` is missing from the IOKit battery properties.
+pub(crate) fn merge(mut info: BatteryInfo) -> BatteryInfo {
+    let Some(smart) = smart_battery() else {
+        return info;
+    };
+    let watt_hours = |mah: Option<f32>| Some(mah? * smart.voltage? / 1_000_000.0);
+    let raw_full = watt_hours(smart.raw_max_capacity).filter(|&wh| wh > 0.0);
+    let design = watt_hours(smart.design_capacity).filter(|&wh| wh > 0.0);
+
+    let unset = |value: Option<f32>| value.is_none_or(|v| v <= 0.0);
+    if unset(info.energy_full) {
+        info.energy_full = raw_full;
+    }
+    if unset(info.energy_full_design) {
+        info.energy_full_design = design;
+    }
+    if unset(info.health) {
+        info.health = raw_full
+            .zip(design)
+            .map(|(full, design)| full / design * 100.0);
+    }
+    if info.temperature.is_none() {
+        info.temperature = smart.temperature.map(|hundredths| hundredths / 100.0);
+    }
+    if info.state == State::Unknown {
+        let full = smart
+            .raw_current_capacity
+            .zip(smart.raw_max_capacity)
+            .is_some_and(|(now, max)| now >= max);
+        info.state = match (smart.is_charging, smart.external_connected) {
+            (Some(true), _) => State::Charging,
+            (_, Some(true)) if full => State::Full,
+            (_, Some(false)) => State::Discharging,
+            _ => State::Unknown,
+        };
+    }
+    info
+}
+
+fn smart_battery() -> Option<SmartBattery> {
+    // SAFETY: the matching dictionary is consumed by IOServiceGetMatchingService, the
+    // service is released once its properties are copied, and the properties follow
+    // the create rule so the wrapper takes ownership of them.
+    let properties = unsafe {
+        let service = IOServiceGetMatchingService(
+            kIOMasterPortDefault,
+            IOServiceMatching(c"AppleSmartBattery".as_ptr()),
+        );
+        if service == 0 {
+            return None;
+        }
+        let mut properties: CFMutableDictionaryRef = ptr::null_mut();
+        let result =
+            IORegistryEntryCreateCFProperties(service, &mut properties, kCFAllocatorDefault, 0);
+        IOObjectRelease(service);
+        if result != 0 || properties.is_null() {
+            return None;
+        }
+        CFDictionary::<CFString, CFType>::wrap_under_create_rule(properties)
+    };
+
+    let value = |key: &'static str| properties.find(CFString::from_static_string(key));
+    let number = |key| {
+        value(key)?
+            .downcast::<CFNumber>()?
+            .to_f64()
+            .map(|n| n as f32)
+    };
+    let flag = |key| value(key)?.downcast::<CFBoolean>().map(bool::from);
+    Some(SmartBattery {
+        raw_current_capacity: number("AppleRawCurrentCapacity"),
+        raw_max_capacity: number("AppleRawMaxCapacity"),
+        design_capacity: number("DesignCapacity"),
+        voltage: number("Voltage"),
+        is_charging: flag("IsCharging"),
+        external_connected: flag("ExternalConnected"),
+        temperature: number("Temperature"),
+    })
+}
@@ -0,0 +1,91 @@
+//! How the TUI loop waits for input between redraws.
+
+use std::io;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use crossterm::event::{self, Event, KeyEvent};
+
+/// How often the `sleep` strategy's input thread checks for key presses.
+const INPUT_POLL: Duration = Duration::from_millis(20);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum PollStrategy {
+    /// Block in crossterm's event poll until a key arrives or the interval passes
+    #[default]
+    Event,
+    /// Read keys on a background thread and wake the loop from a ticker thread
+    Sleep,
+}
+
+enum Wake {
+    Tick,
+    Key(KeyEvent),
+}
+
+pub struct Waiter {
+    /// Wake-ups from the `sleep` strategy's threads; `None` polls crossterm directly.
+    wakes: Option<Receiver<Wake>>,
+}
+
+impl Waiter {
+    /// Starts the background threads for `PollStrategy::Sleep`, ticking every `tick`.
+    pub fn new(strategy: PollStrategy, tick: Duration) -> Self {
+        let wakes = match strategy {
+            PollStrategy::Event => None,
+            PollStrategy::Sleep => {
+                let (tx, rx) = mpsc::channel();
+                let ticks = tx.clone();
+                thread::spawn(move || {
+                    while ticks.send(Wake::Tick).is_ok() {
+                        thread::sleep(tick);
+                    }
+                });
+                thread::spawn(move || read_keys(&tx));
+                Some(rx)
+            }
+        };
+        Self { wakes }
+    }
+
+    /// Waits up to `timeout` for a key, returning `None` on timeout or a tick.
+    pub fn next_key(&self, timeout: Duration) -> io::Result<Option<KeyEvent>> {
+        match &self.wakes {
+            None => {
+                if event::poll(timeout)?
+                    && let Event::Key(key) = event::read()?
+                {
+                    return Ok(Some(key));
+                }
+                Ok(None)
+            }
+            Some(rx) => match rx.recv_timeout(timeout) {
+                Ok(Wake::Key(key)) => Ok(Some(key)),
+                Ok(Wake::Tick) | Err(RecvTimeoutError::Timeout) => Ok(None),
+                Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(
+                    io::ErrorKind::BrokenPipe,
+                    "input thread stopped",
+                )),
+            },
+        }
+    }
+}
+
+/// Forwards key presses until the loop goes away or the terminal can't be read.
+fn read_keys(tx: &Sender<Wake>) {
+    loop {
+        match event::poll(INPUT_POLL) {
+            Ok(true) => {
+                if let Ok(Event::Key(key)) = event::read()
+                    && tx.send(Wake::Key(key)).is_err()
+                {
+                    return;
+                }
+            }
+            Ok(false) => {}
+            Err(_) => return,
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use battery::{
+    Manager, State,
+    units::{
+        energy::watt_hour, power::watt, ratio::percent, thermodynamic_temperature::degree_celsius,
+    },
+};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// A single battery reading, independent of where it came from.
+///
+/// Serialized as `--format json`, which `--replay-file` reads back.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct BatteryInfo {
+    pub charge: f32,
+    #[serde(with = "state_name")]
+    pub state: State,
+    /// Charge or discharge rate in watts.
+    pub energy_rate: Option<f32>,
+    /// Temperature in degrees Celsius, when the platform reports it.
+    pub temperature: Option<f32>,
+    /// State of health in percent.
+    pub health: Option<f32>,
+    /// Capacity when fully charged, in watt-hours.
+    pub energy_full: Option<f32>,
+    /// Capacity the battery was designed for, in watt-hours.
+    pub energy_full_design: Option<f32>,
+}
+
+/// `State` as its lowercase name, e.g. `"discharging"`; any case is accepted back.
+mod state_name {
+    use battery::State;
+    use serde::{Deserialize, Deserializer, Serializer, de};
+
+    pub fn serialize<S: Serializer>(state: &State, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(state)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<State, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| de::Error::custom(format!("`{name}` is not a battery state")))
+    }
+}
+
+/// Source of battery readings for the display.
+pub trait BatteryProvider {
+    /// The current reading, or `Ok(None)` when there is no battery.
+    fn battery_info(&self) -> Result<Option<BatteryInfo>, battery::Error>;
+
+    /// Whether a finite source has run out of readings.
+    fn exhausted(&self) -> bool {
+        false
+    }
+}
+
+/// One row of `--list`.
+pub struct BatteryListing {
+    pub vendor: String,
+    pub model: String,
+    pub technology: String,
+    pub charge: f32,
+}
+
+/// Reads the first battery reported by the operating system.
+pub struct SystemBatteryProvider {
+    manager: Manager,
+}
+
+impl SystemBatteryProvider {
+    pub fn new() -> Result<Self, battery::Error> {
+        Ok(Self {
+            manager: Manager::new()?,
+        })
+    }
+
+    /// Checks for a battery up to `attempts` times, sleeping `interval` between tries.
+    /// Returns whether one was found.
+    pub fn wait_for_battery(&self, attempts: u32, interval: Duration) -> bool {
+        for attempt in 1..=attempts {
+            match self
+                .manager
+                .batteries()
+                .map(|mut batteries| batteries.next())
+            {
+                Ok(Some(Ok(_))) => return true,
+                Ok(Some(Err(err))) | Err(err) => {
+                    debug!("startup attempt {attempt}/{attempts}: {err}");
+                }
+                Ok(None) => debug!("startup attempt {attempt}/{attempts}: no battery"),
+            }
+            if attempt < attempts {
+                thread::sleep(interval);
+            }
+        }
+        false
+    }
+
+    /// Every battery the operating system reports, in its order.
+    pub fn list(&self) -> Result<Vec<BatteryListing>, battery::Error> {
+        self.manager
+            .batteries()?
+            .map(|battery| {
+                let b = battery?;
+                Ok(BatteryListing {
+                    vendor: b.vendor().unwrap_or("-").trim().to_string(),
+                    model: b.model().unwrap_or("-").trim().to_string(),
+                    technology: b.technology().to_string(),
+                    charge: b.state_of_charge().get::<percent>(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl BatteryProvider for SystemBatteryProvider {
+    fn battery_info(&self) -> Result<Option<BatteryInfo>, battery::Error> {
+        let Some(battery) = self.manager.batteries()?.next() else {
+            return Ok(None);
+        };
+        let b = battery?;
+        let info = BatteryInfo {
+            charge: b.state_of_charge().get::<percent>(),
+            state: b.state(),
+            energy_rate: Some(b.energy_rate().get::<watt>()),
+            temperature: b.temperature().map(|t| t.get::<degree_celsius>()),
+            health: Some(b.state_of_health().get::<percent>()),
+            energy_full: Some(b.energy_full().get::<watt_hour>()),
+            energy_full_design: Some(b.energy_full_design().get::<watt_hour>()),
+        };
+        #[cfg(target_os = "macos")]
+        let info = crate::platform::macos::merge(info);
+        Ok(Some(info))
+    }
+}
+
+/// Reads the first battery in a Linux `power_supply` sysfs tree, such as a test fixture
+/// laid out like `/sys/class/power_supply` with `BAT0/status`, `BAT0/capacity` and so on.
+pub struct SysfsBatteryProvider {
+    root: PathBuf,
+}
+
+impl SysfsBatteryProvider {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// The first supply directory, by name, whose `type` is `Battery`.
+    fn battery_dir(&self) -> io::Result<Option<PathBuf>> {
+        let mut dirs: Vec<PathBuf> = fs::read_dir(&self.root)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|dir| read_attr(dir, "type").as_deref() == Some("Battery"))
+            .collect();
+        dirs.sort();
+        Ok(dirs.into_iter().next())
+    }
+}
+
+impl BatteryProvider for SysfsBatteryProvider {
+    fn battery_info(&self) -> Result<Option<BatteryInfo>, battery::Error> {
+        Ok(self.battery_dir()?.as_deref().and_then(read_supply))
+    }
+}
+
+/// Reads a supply directory, or `None` if it doesn't report a charge.
+fn read_supply(dir: &Path) -> Option<BatteryInfo> {
+    let micro = |name: &str| read_number(dir, name).map(|v| v / 1_000_000.0);
+    let voltage = micro("voltage_now");
+    // Drivers report either energy (µWh) or charge (µAh); convert charge with the voltage.
+    let watt_hours =
+        |energy: &str, charge: &str| micro(energy).or_else(|| Some(micro(charge)? * voltage?));
+
+    let energy_now = watt_hours("energy_now", "charge_now");
+    let energy_full = watt_hours("energy_full", "charge_full");
+    let energy_full_design = watt_hours("energy_full_design", "charge_full_design");
+    let charge = read_number(dir, "capacity")
+        .or_else(|| Some(energy_now? / energy_full.filter(|&wh| wh > 0.0)? * 100.0))?;
+    let state = match read_attr(dir, "status").as_deref() {
+        Some("Charging") => State::Charging,
+        Some("Discharging") => State::Discharging,
+        Some("Full") => State::Full,
+        Some("Empty") => State::Empty,
+        _ => State::Unknown,
+    };
+
+    Some(BatteryInfo {
+        charge,
+        state,
+        energy_rate: micro("power_now")
+            .or_else(|| Some(micro("current_now")? * voltage?))
+            .map(f32::abs),
+        temperature: read_number(dir, "temp").map(|tenths| tenths / 10.0),
+        health: energy_full
+            .zip(energy_full_design.filter(|&wh| wh > 0.0))
+            .map(|(full, design)| full / design * 100.0),
+        energy_full,
+        energy_full_design,
+    })
+}
+
+fn read_attr(dir: &Path, name: &str) -> Option<String> {
+    Some(fs::read_to_string(dir.join(name)).ok()?.trim().to_string())
+}
+
+fn read_number(dir: &Path, name: &str) -> Option<f32> {
+    read_attr(dir, name)?.parse().ok()
+}
+
+/// Plays back recorded readings, one per refresh, from a JSON array or from
+/// `--format json` lines.
+pub struct ReplayBatteryProvider {
+    readings: Vec<BatteryInfo>,
+    next: Cell<usize>,
+    looping: bool,
+}
+
+impl ReplayBatteryProvider {
+    pub fn load(path: &Path, looping: bool) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let readings = serde_json::from_str::<Vec<BatteryInfo>>(&text).or_else(|_| {
+            text.lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(serde_json::from_str)
+                .collect::<Result<Vec<_>, _>>()
+        });
+        let invalid = |message: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {message}", path.display()),
+            )
+        };
+        match readings {
+            Ok(readings) if readings.is_empty() => Err(invalid("no readings".to_string())),
+            Ok(readings) => Ok(Self {
+                readings,
+                next: Cell::new(0),
+                looping,
+            }),
+            Err(err) => Err(invalid(err.to_string())),
+        }
+    }
+}
+
+impl BatteryProvider for ReplayBatteryProvider {
+    fn battery_info(&self) -> Result<Option<BatteryInfo>, battery::Error> {
+        let mut index = self.next.get();
+        if index == self.readings.len() && self.looping {
+            index = 0;
+        }
+        // Once finished without looping, the last reading stays on display.
+        self.next.set((index + 1).min(self.readings.len()));
+        Ok(self.readings.get(index).or(self.readings.last()).copied())
+    }
+
+    fn exhausted(&self) -> bool {
+        !self.looping && self.next.get() == self.readings.len()
+    }
+}
+
+/// Always reports the same reading, for trying out display modes without real hardware.
+pub struct SimulatedBatteryProvider {
+    info: BatteryInfo,
+}
+
+impl SimulatedBatteryProvider {
+    pub fn new(info: BatteryInfo) -> Self {
+        Self { info }
+    }
+}
+
+impl BatteryProvider for SimulatedBatteryProvider {
+    fn battery_info(&self) -> Result<Option<BatteryInfo>, battery::Error> {
+        Ok(Some(self.info))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_sysfs_fixture() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/sysfs");
+        let info = SysfsBatteryProvider::new(root)
+            .battery_info()
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.charge, 74.0);
+        assert_eq!(info.state, State::Discharging);
+        assert_eq!(info.energy_rate, Some(8.5));
+        assert_eq!(info.temperature, Some(31.5));
+        assert_eq!(info.energy_full, Some(50.0));
+        assert_eq!(info.energy_full_design, Some(57.0));
+    }
+
+    #[test]
+    fn replays_json_lines_then_stops() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay.json");
+        let replay = ReplayBatteryProvider::load(&path, false).unwrap();
+        let states: Vec<_> = (0..3)
+            .map(|_| replay.battery_info().unwrap().unwrap().state)
+            .collect();
+        assert_eq!(
+            states,
+            [State::Discharging, State::Charging, State::Charging]
+        );
+        assert!(replay.exhausted());
+    }
+
+    #[test]
+    fn looping_replay_is_never_exhausted() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/replay.json");
+        let replay = ReplayBatteryProvider::load(&path, true).unwrap();
+        let states: Vec<_> = (0..3)
+            .map(|_| {
+                let state = replay.battery_info().unwrap().unwrap().state;
+                assert!(!replay.exhausted());
+                state
+            })
+            .collect();
+        assert_eq!(
+            states,
+            [State::Discharging, State::Charging, State::Discharging]
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use clap::ValueEnum;
+use ratatui::style::Color;
+
+/// Red, green and blue channels.
+pub type Rgb = (u8, u8, u8);
+
+/// The 16 basic CSS colors plus a few friendlier extras, as `(name, rgb)`.
+const NAMED_COLORS: [(&str, Rgb); 25] = [
+    ("black", (0, 0, 0)),
+    ("silver", (192, 192, 192)),
+    ("gray", (128, 128, 128)),
+    ("grey", (128, 128, 128)),
+    ("white", (255, 255, 255)),
+    ("maroon", (128, 0, 0)),
+    ("red", (255, 0, 0)),
+    ("purple", (128, 0, 128)),
+    ("fuchsia", (255, 0, 255)),
+    ("magenta", (255, 0, 255)),
+    ("green", (0, 128, 0)),
+    ("lime", (0, 255, 0)),
+    ("olive", (128, 128, 0)),
+    ("yellow", (255, 255, 0)),
+    ("navy", (0, 0, 128)),
+    ("blue", (0, 0, 255)),
+    ("teal", (0, 128, 128)),
+    ("aqua", (0, 255, 255)),
+    ("cyan", (0, 255, 255)),
+    ("orange", (255, 165, 0)),
+    ("limegreen", (50, 205, 50)),
+    ("orangered", (255, 69, 0)),
+    ("dodgerblue", (30, 144, 255)),
+    ("gold", (255, 215, 0)),
+    ("crimson", (220, 20, 60)),
+];
+
+/// The RGB value of a CSS color name such as `limegreen`, ignoring case.
+pub fn parse_named_color(s: &str) -> Option<Rgb> {
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(s.trim()))
+        .map(|&(_, rgb)| rgb)
+}
+
+/// How the widget picks its color.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Green, yellow or red by charge level.
+    #[default]
+    ByCharge,
+    /// `--two-color`: a blend from the low color at empty to the high color at full.
+    TwoColor { high: Rgb, low: Rgb },
+    /// `--color-state-only`: by charging state, ignoring the charge level.
+    ByState,
+}
+
+/// Glyphs used to draw the `--graphic` bar.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum BarStyle {
+    /// `[████░░░░░░]`
+    #[default]
+    Block,
+    /// `[▰▰▰▰▱▱▱▱▱▱]`, one cell per segment
+    Segments,
+    /// `[━━━━──────]`
+    Thin,
+    /// `[⣿⣿⣿⣿⣀⣀⣀⣀⣀⣀]`
+    Braille,
+}
+
+impl BarStyle {
+    pub fn filled(self) -> &'static str {
+        match self {
+            Self::Block => "█",
+            Self::Segments => "▰",
+            Self::Thin => "━",
+            Self::Braille => "⣿",
+        }
+    }
+
+    pub fn empty(self) -> &'static str {
+        match self {
+            Self::Block => "░",
+            Self::Segments => "▱",
+            Self::Thin => "─",
+            Self::Braille => "⣀",
+        }
+    }
+}
+
+/// Left-aligned partial blocks from 1/8 to 7/8 of a cell, for the `--unicode-bar` edge.
+const EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// A `width`-cell bar filled to `charge` percent at 1/8-cell resolution, padded with spaces.
+pub fn smooth_bar(charge: f32, width: usize) -> String {
+    let eighths = (charge.clamp(0.0, 100.0) / 100.0 * (width * 8) as f32).round() as usize;
+    let (full, partial) = (eighths / 8, eighths % 8);
+    let mut bar = "█".repeat(full);
+    if partial > 0 {
+        bar.push(EIGHTHS[partial - 1]);
+    }
+    let used = full + usize::from(partial > 0);
+    bar.push_str(&" ".repeat(width - used));
+    bar
+}
+
+/// Animation applied to the bar while charging.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ChargingAnimation {
+    #[default]
+    None,
+    /// Fade the bar between full and dim brightness
+    Pulse,
+    /// Move a highlight across the filled cells
+    Sweep,
+}
+
+/// One period of `(1 + cos x) / 2` scaled to 0–255, so pulses start at full brightness.
+const PULSE_CURVE: [u8; 32] = [
+    255, 253, 245, 234, 218, 198, 176, 152, 128, 103, 79, 57, 37, 21, 10, 2, 0, 2, 10, 21, 37, 57,
+    79, 103, 127, 152, 176, 198, 218, 234, 245, 253,
+];
+
+/// Lowest brightness of a pulse, out of 255.
+const PULSE_FLOOR: u32 = 90;
+
+/// RGB components of `color`, approximating named colors with common terminal defaults.
+pub fn rgb(color: Color) -> Rgb {
+    match color {
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::DarkGray => (127, 127, 127),
+        _ => (229, 229, 229),
+    }
+}
+
+/// The closest entry in the 6×6×6 color cube of the 256-color palette.
+pub fn indexed(color: Rgb) -> Color {
+    let level = |channel: u8| ((u16::from(channel) * 5 + 127) / 255) as u8;
+    Color::Indexed(16 + 36 * level(color.0) + 6 * level(color.1) + level(color.2))
+}
+
+/// Scales `base` by a sine-shaped brightness that completes one cycle every `period` ticks.
+pub fn pulse_color(base: Rgb, tick: u64, period: u64) -> Color {
+    let period = period.max(1);
+    let index = (tick % period * PULSE_CURVE.len() as u64 / period) as usize;
+    let level = PULSE_FLOOR + u32::from(PULSE_CURVE[index]) * (255 - PULSE_FLOOR) / 255;
+    let scale = |channel: u8| (u32::from(channel) * level / 255) as u8;
+    Color::Rgb(scale(base.0), scale(base.1), scale(base.2))
+}
+
+/// Mixes `low` (at `t = 0`) into `high` (at `t = 1`) channel by channel.
+pub fn two_color_lerp(high: Rgb, low: Rgb, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |h: u8, l: u8| (f32::from(l) + (f32::from(h) - f32::from(l)) * t).round() as u8;
+    Color::Rgb(mix(high.0, low.0), mix(high.1, low.1), mix(high.2, low.2))
+}
@@ -0,0 +1,38 @@
+//! `--refresh-on-wake`: notices resume from suspend so the battery is re-read at once.
+
+use std::fs;
+
+const WAKEUP_COUNT_PATH: &str = "/sys/power/wakeup_count";
+
+/// Detects resume from suspend via the kernel's wakeup counter (Linux only).
+pub struct SuspendDetector {
+    last_count: u64,
+}
+
+impl SuspendDetector {
+    /// Returns `None` when the wakeup counter isn't available on this system.
+    pub fn new() -> Option<Self> {
+        Some(Self {
+            last_count: read_wakeup_count()?,
+        })
+    }
+
+    /// Returns `true` once for each increase of the wakeup counter since the last check.
+    pub fn check_resumed(&mut self) -> bool {
+        match read_wakeup_count() {
+            Some(count) if count > self.last_count => {
+                self.last_count = count;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn read_wakeup_count() -> Option<u64> {
+    fs::read_to_string(WAKEUP_COUNT_PATH)
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
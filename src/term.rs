@@ -0,0 +1,62 @@
+//! Guesses at what the terminal can draw, from its environment variables.
+
+use std::env;
+
+/// What the terminal is expected to render correctly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TermCapabilities {
+    /// 24-bit colors; without it RGB colors are approximated from the 256-color palette.
+    pub true_color: bool,
+    /// Emoji at their usual double width.
+    pub emoji: bool,
+    /// Block elements and other non-ASCII glyphs.
+    pub unicode: bool,
+}
+
+impl TermCapabilities {
+    /// Everything on, for `--no-term-detect`.
+    pub fn assumed() -> Self {
+        Self {
+            true_color: true,
+            emoji: true,
+            unicode: true,
+        }
+    }
+}
+
+/// Reads `TERM`, `COLORTERM`, `VTE_VERSION` and `TERM_PROGRAM`, plus the locale for emoji.
+pub fn detect_capabilities() -> TermCapabilities {
+    let var = |name| env::var(name).unwrap_or_default();
+    let term = var("TERM");
+    let program = var("TERM_PROGRAM");
+    // VTE has supported 24-bit color since 0.36, reported as 3600.
+    let vte = var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 3600);
+    let true_color = matches!(var("COLORTERM").as_str(), "truecolor" | "24bit")
+        || vte
+        || matches!(program.as_str(), "iTerm.app" | "WezTerm" | "vscode")
+        || term.ends_with("-direct")
+        || matches!(term.as_str(), "xterm-kitty" | "alacritty");
+    TermCapabilities {
+        true_color,
+        emoji: supports_emoji(&term),
+        unicode: term != "xterm-mono",
+    }
+}
+
+/// Whether emoji will render at their usual width, judging by `TERM`, `LC_ALL` and `LANG`.
+fn supports_emoji(term: &str) -> bool {
+    let locale = ["LC_ALL", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default()
+        .to_lowercase();
+    if !locale.contains("utf-8") && !locale.contains("utf8") {
+        return false;
+    }
+    // Bare `xterm` (rather than `xterm-256color`) usually means an old or minimal terminal.
+    !matches!(
+        term,
+        "" | "dumb" | "linux" | "vt100" | "vt220" | "ansi" | "xterm" | "xterm-mono"
+    )
+}
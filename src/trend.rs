@@ -0,0 +1,83 @@
+//! Short-term charge trend used for forecasts.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Number of recent readings the trend is fitted over.
+const TREND_READINGS: usize = 10;
+
+/// Number of consecutive-reading rates averaged by [`Trend::charge_rate`].
+const RATE_SMOOTHING: usize = 3;
+
+/// Minimum readings before a forecast is attempted.
+const MIN_FORECAST_READINGS: usize = 3;
+
+#[derive(Default)]
+pub struct Trend {
+    readings: VecDeque<(f32, Instant)>,
+}
+
+impl Trend {
+    pub fn push(&mut self, charge: f32, at: Instant) {
+        if self.readings.len() == TREND_READINGS {
+            self.readings.pop_front();
+        }
+        self.readings.push_back((charge, at));
+    }
+
+    /// Rate of change in percent per minute, averaged over the last few readings to
+    /// smooth out one-tick spikes. Negative while discharging.
+    pub fn charge_rate(&self) -> Option<f32> {
+        let skip = self.readings.len().saturating_sub(RATE_SMOOTHING + 1);
+        let recent: Vec<_> = self.readings.iter().skip(skip).collect();
+        let rates: Vec<f32> = recent
+            .windows(2)
+            .filter_map(|pair| {
+                let (&(previous, then), &(current, now)) = (pair[0], pair[1]);
+                let elapsed = now.duration_since(then).as_secs_f32();
+                (elapsed > 0.0).then(|| (current - previous) / elapsed * 60.0)
+            })
+            .collect();
+        if rates.is_empty() {
+            return None;
+        }
+        Some(rates.iter().sum::<f32>() / rates.len() as f32)
+    }
+
+    /// Time until the charge reaches `target` percent at the current rate.
+    pub fn forecast(&self, target: f32) -> Option<Duration> {
+        let readings: Vec<_> = self.readings.iter().copied().collect();
+        linear_forecast(&readings, target)
+    }
+}
+
+/// Fits a least-squares line through `readings` and extrapolates when it reaches `target`,
+/// measured from the latest reading.
+///
+/// Returns `None` with fewer than three readings, or when the charge isn't moving towards
+/// `target` (including a flat trend, which extrapolates to infinity).
+pub fn linear_forecast(readings: &[(f32, Instant)], target: f32) -> Option<Duration> {
+    if readings.len() < MIN_FORECAST_READINGS {
+        return None;
+    }
+    let start = readings[0].1;
+    let points: Vec<(f64, f64)> = readings
+        .iter()
+        .map(|&(charge, at)| (at.duration_since(start).as_secs_f64(), f64::from(charge)))
+        .collect();
+
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let covariance: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        return None;
+    }
+    let slope = covariance / variance;
+
+    let (last_x, _) = points[points.len() - 1];
+    let fitted_now = mean_y + slope * (last_x - mean_x);
+    let remaining = (f64::from(target) - fitted_now) / slope;
+    Duration::try_from_secs_f64(remaining).ok()
+}
@@ -0,0 +1,84 @@
+//! `--version-check`: compares the running version with the latest release on crates.io.
+
+use std::fmt;
+use std::process::ExitCode;
+
+use serde::Deserialize;
+
+const CRATE_URL: &str = "https://crates.io/api/v1/crates/little-bat";
+const CURRENT: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Deserialize)]
+struct CrateInfo {
+    max_stable_version: Option<String>,
+    max_version: String,
+}
+
+#[derive(Debug)]
+enum CheckError {
+    Request(ureq::Error),
+    Parse(serde_json::Error),
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CheckError::Request(err) => write!(f, "could not reach crates.io: {err}"),
+            CheckError::Parse(err) => write!(f, "unexpected response from crates.io: {err}"),
+        }
+    }
+}
+
+/// Prints whether a newer release exists.
+pub fn run() -> ExitCode {
+    match latest_version() {
+        Ok(latest) if is_newer(&latest, CURRENT) => {
+            println!("A new version {latest} is available (you have {CURRENT})");
+            ExitCode::SUCCESS
+        }
+        Ok(_) => {
+            println!("little-bat {CURRENT} is up to date");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("little-bat: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn latest_version() -> Result<String, CheckError> {
+    let body = ureq::get(CRATE_URL)
+        // crates.io rejects requests without a user agent.
+        .header(
+            "User-Agent",
+            concat!("little-bat/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(CheckError::Request)?;
+    let info = serde_json::from_str::<CrateResponse>(&body)
+        .map_err(CheckError::Parse)?
+        .krate;
+    Ok(info.max_stable_version.unwrap_or(info.max_version))
+}
+
+/// Compares dotted numeric versions, ignoring any pre-release or build suffix.
+fn is_newer(latest: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    parse(latest) > parse(current)
+}